@@ -0,0 +1,56 @@
+package diskstore
+
+import (
+	"io"
+	"os"
+	"unsafe"
+)
+
+// directAlign is the buffer, offset and length alignment used for O_DIRECT
+// I/O. 4 KiB covers the logical block size of common SSDs and NVMe drives.
+const directAlign = 4096
+
+// alignedBuf returns a slice of length n whose backing array starts on a
+// directAlign boundary and whose capacity is n rounded up to directAlign.
+func alignedBuf(n int) []byte {
+	size := (n + directAlign - 1) &^ (directAlign - 1)
+	raw := make([]byte, size+directAlign)
+	off := 0
+	if rem := int(uintptr(unsafe.Pointer(&raw[0])) & (directAlign - 1)); rem != 0 {
+		off = directAlign - rem
+	}
+	return raw[off : off+n : off+size]
+}
+
+// readFileDirect reads the whole file at path bypassing the page cache.
+func readFileDirect(path string) ([]byte, error) {
+	f, err := openDirect(path, os.O_RDONLY, 0)
+	if err != nil {
+		return nil, err
+	}
+	defer f.Close()
+
+	fi, err := f.Stat()
+	if err != nil {
+		return nil, err
+	}
+	buf := alignedBuf(int(fi.Size()))
+	full := buf[:cap(buf)]
+
+	// Reads stay block-aligned; only the final one comes back short.
+	var n int
+	for n < len(buf) {
+		m, err := f.Read(full[n:])
+		n += m
+		if err == io.EOF {
+			break
+		}
+		if err != nil {
+			return nil, err
+		}
+	}
+	if n < len(buf) {
+		return nil, io.ErrUnexpectedEOF
+	}
+	return buf, nil
+}
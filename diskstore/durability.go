@@ -0,0 +1,164 @@
+package diskstore
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"path/filepath"
+	"sync"
+)
+
+// SyncPolicy controls how block writes reach stable storage.
+type SyncPolicy int
+
+const (
+	// SyncNone leaves writeback to the OS; a crash can lose recent blocks.
+	SyncNone SyncPolicy = iota
+	// SyncData fdatasyncs every block before Put or a migration completes.
+	SyncData
+	// SyncBatch starts writeback of each block with sync_file_range and
+	// waits for all of it in one pass at Sync and when the index is saved.
+	SyncBatch
+)
+
+var syncPolicyNames = [...]string{SyncNone: "none", SyncData: "fdatasync", SyncBatch: "batch"}
+
+func (p SyncPolicy) String() string {
+	if p < 0 || int(p) >= len(syncPolicyNames) {
+		return fmt.Sprintf("SyncPolicy(%d)", int(p))
+	}
+	return syncPolicyNames[p]
+}
+
+// ParseSyncPolicy parses "none", "fdatasync" or "batch".
+func ParseSyncPolicy(s string) (SyncPolicy, error) {
+	for p, name := range syncPolicyNames {
+		if s == name {
+			return SyncPolicy(p), nil
+		}
+	}
+	return SyncNone, fmt.Errorf("diskstore: unknown sync policy %q", s)
+}
+
+// syncer applies a SyncPolicy. It has its own lock because remote writes
+// run on remoteIO goroutines.
+type syncer struct {
+	policy SyncPolicy
+
+	mu    sync.Mutex
+	dirty map[string]struct{} // files and directories awaiting fdatasync under SyncBatch
+}
+
+// sync is called on f after n bytes were written at off.
+func (y *syncer) sync(f *os.File, off, n int64) error {
+	switch y.policy {
+	case SyncData:
+		return fdatasync(f)
+	case SyncBatch:
+		startWriteback(f, off, n)
+	}
+	return nil
+}
+
+// committed makes a file newly created or renamed into place durable: its
+// directory entry now, or both it and the entry at the next flush.
+func (y *syncer) committed(path string) error {
+	switch y.policy {
+	case SyncData:
+		return syncPath(filepath.Dir(path))
+	case SyncBatch:
+		y.mark(path)
+		y.mark(filepath.Dir(path))
+	}
+	return nil
+}
+
+// mark queues path for the next flush under SyncBatch.
+func (y *syncer) mark(path string) {
+	if y.policy != SyncBatch {
+		return
+	}
+	y.mu.Lock()
+	if y.dirty == nil {
+		y.dirty = make(map[string]struct{})
+	}
+	y.dirty[path] = struct{}{}
+	y.mu.Unlock()
+}
+
+// flush fdatasyncs everything queued since the last flush. Files removed in
+// the meantime are skipped.
+func (y *syncer) flush() error {
+	y.mu.Lock()
+	dirty := y.dirty
+	y.dirty = nil
+	y.mu.Unlock()
+
+	var first error
+	for path := range dirty {
+		if err := syncPath(path); err != nil && !errors.Is(err, os.ErrNotExist) && first == nil {
+			first = err
+		}
+	}
+	return first
+}
+
+func syncPath(path string) error {
+	f, err := os.Open(path)
+	if err != nil {
+		return err
+	}
+	defer f.Close()
+	return fdatasync(f)
+}
+
+// writeFileAtomic writes data to a temporary file beside path and renames
+// it into place, so a crash leaves either the old file or the complete new
+// one. Each call gets its own temporary file, so a remote attempt abandoned
+// mid-write never shares one with the attempt that replaced it. With direct
+// set the write bypasses the page cache; it is padded to whole blocks and
+// the file truncated back to len(data).
+func writeFileAtomic(path string, data []byte, direct bool, y *syncer) error {
+	f, err := os.CreateTemp(filepath.Dir(path), filepath.Base(path)+".*.tmp")
+	if err != nil {
+		return err
+	}
+	tmp := f.Name()
+	err = f.Chmod(0644)
+	if err == nil && direct {
+		f.Close()
+		f, err = openDirect(tmp, os.O_WRONLY, 0644)
+	}
+	if err != nil {
+		if f != nil {
+			f.Close()
+		}
+		os.Remove(tmp)
+		return err
+	}
+
+	if direct {
+		buf := alignedBuf(len(data))
+		copy(buf, data)
+		_, err = f.Write(buf[:cap(buf)])
+		if err == nil {
+			err = f.Truncate(int64(len(data)))
+		}
+	} else {
+		_, err = f.Write(data)
+	}
+	if err == nil {
+		err = y.sync(f, 0, int64(len(data)))
+	}
+	if cerr := f.Close(); err == nil {
+		err = cerr
+	}
+	if err == nil {
+		err = os.Rename(tmp, path)
+	}
+	if err != nil {
+		os.Remove(tmp)
+		return err
+	}
+	return y.committed(path)
+}
@@ -0,0 +1,128 @@
+package diskstore
+
+import (
+	"fmt"
+	"math"
+	"os"
+	"strconv"
+	"strings"
+	"time"
+)
+
+// EnvPrefix prefixes the environment variables read by Config.LoadEnv.
+const EnvPrefix = "OLLAMA_KV_TIER_"
+
+// envVars maps each variable, minus EnvPrefix, to the Config field it sets.
+var envVars = []struct {
+	name string
+	set  func(c *Config, v string) error
+}{
+	{"LOCAL", func(c *Config, v string) error { c.LocalPath = v; return nil }},
+	{"REMOTE", func(c *Config, v string) error { c.RemotePath = v; return nil }},
+	{"REMOTE_MOUNTS", setRemoteMounts},
+	{"LOCAL_GB", func(c *Config, v string) error { return setBudget(&c.LocalBudget, v) }},
+	{"REMOTE_GB", func(c *Config, v string) error { return setBudget(&c.RemoteBudget, v) }},
+	{"AUTO_HEADROOM", setHeadroom},
+	{"COMPRESS", func(c *Config, v string) error { return setBool(&c.Compress, v) }},
+	{"DIRECT_IO", func(c *Config, v string) error { return setBool(&c.DirectIO, v) }},
+	{"MMAP_READS", func(c *Config, v string) error { return setBool(&c.MmapReads, v) }},
+	{"DURABILITY", func(c *Config, v string) (err error) { c.Durability, err = ParseSyncPolicy(v); return err }},
+	{"LOCAL_IDLE", func(c *Config, v string) error { return setDuration(&c.LocalIdle, v) }},
+	{"TTL", func(c *Config, v string) error { return setDuration(&c.TTL, v) }},
+	{"SLAB_SIZE", func(c *Config, v string) error { return setSize(&c.SlabSize, v) }},
+	{"REMOTE_RETRIES", func(c *Config, v string) (err error) { c.RemoteRetries, err = strconv.Atoi(v); return err }},
+	{"REMOTE_TIMEOUT", func(c *Config, v string) error { return setDuration(&c.RemoteTimeout, v) }},
+}
+
+// LoadEnv overrides fields of c from OLLAMA_KV_TIER_* environment
+// variables. Unset variables leave their field alone, so the environment
+// layers over whatever the caller configured. Sizes take units as in
+// ParseSize; a bare number is GiB for budgets, for compatibility, and bytes
+// otherwise. Budgets may also be "auto" for AutoBudget. REMOTE_MOUNTS is a
+// comma-separated list of path[:weight] appended to RemoteMounts. Durations use
+// time.ParseDuration syntax, and Durability takes "none", "fdatasync" or
+// "batch".
+func (c *Config) LoadEnv() error {
+	for _, ev := range envVars {
+		v, ok := os.LookupEnv(EnvPrefix + ev.name)
+		if !ok {
+			continue
+		}
+		if err := ev.set(c, v); err != nil {
+			return fmt.Errorf("diskstore: %s%s=%q: %w", EnvPrefix, ev.name, v, err)
+		}
+	}
+	return nil
+}
+
+func setBudget(dst *int64, v string) error {
+	if v == "auto" {
+		*dst = AutoBudget
+		return nil
+	}
+	if gb, err := strconv.ParseInt(v, 10, 64); err == nil {
+		if gb < 0 || gb > math.MaxInt64>>30 {
+			return fmt.Errorf("diskstore: invalid size %q", v)
+		}
+		*dst = gb << 30
+		return nil
+	}
+	return setSize(dst, v)
+}
+
+// setRemoteMounts appends path[:weight] entries to c.RemoteMounts. A
+// suffix that does not parse as a number is taken as part of the path.
+func setRemoteMounts(c *Config, v string) error {
+	for _, item := range strings.Split(v, ",") {
+		item = strings.TrimSpace(item)
+		if item == "" {
+			continue
+		}
+		m := RemoteMount{Path: item}
+		if i := strings.LastIndexByte(item, ':'); i > 0 {
+			if w, err := strconv.ParseFloat(item[i+1:], 64); err == nil {
+				if w < 0 {
+					return fmt.Errorf("negative weight for %s", item[:i])
+				}
+				m = RemoteMount{Path: item[:i], Weight: w}
+			}
+		}
+		c.RemoteMounts = append(c.RemoteMounts, m)
+	}
+	return nil
+}
+
+func setHeadroom(c *Config, v string) error {
+	h, err := strconv.ParseFloat(v, 64)
+	if err == nil {
+		err = checkHeadroom(h)
+	}
+	if err == nil {
+		c.AutoHeadroom = h
+	}
+	return err
+}
+
+func setSize(dst *int64, v string) error {
+	n, err := ParseSize(v)
+	if err == nil {
+		*dst = n
+	}
+	return err
+}
+
+func setBool(dst *bool, v string) error {
+	b, err := strconv.ParseBool(v)
+	if err == nil {
+		*dst = b
+	}
+	return err
+}
+
+func setDuration(dst *time.Duration, v string) error {
+	d, err := time.ParseDuration(v)
+	if err == nil {
+		*dst = d
+	}
+	return err
+}
@@ -0,0 +1,95 @@
+package diskstore
+
+import (
+	"sync"
+	"time"
+)
+
+// I/O paths with their own latency histogram.
+const (
+	pathLocalRead = iota
+	pathLocalWrite
+	pathRemoteRead
+	pathRemoteWrite
+	numPaths
+)
+
+var pathNames = [numPaths]string{"local_read", "local_write", "remote_read", "remote_write"}
+
+// Histogram buckets double from 50µs; the last bucket (~26s) also catches
+// anything slower, which is where a stuck NFS call shows up.
+const (
+	firstBucket = 50 * time.Microsecond
+	numBuckets  = 20
+)
+
+// LatencySummary reports quantiles for one I/O path. Quantiles are bucket
+// upper bounds, so they overestimate by at most 2x.
+type LatencySummary struct {
+	Count uint64        `json:"count"`
+	P50   time.Duration `json:"p50"`
+	P95   time.Duration `json:"p95"`
+	P99   time.Duration `json:"p99"`
+}
+
+type histogram struct {
+	mu     sync.Mutex
+	counts [numBuckets]uint64
+	total  uint64
+}
+
+func (h *histogram) observe(d time.Duration) {
+	b, bound := 0, firstBucket
+	for b < numBuckets-1 && d > bound {
+		b++
+		bound *= 2
+	}
+	h.mu.Lock()
+	h.counts[b]++
+	h.total++
+	h.mu.Unlock()
+}
+
+func (h *histogram) summary() LatencySummary {
+	h.mu.Lock()
+	defer h.mu.Unlock()
+	return LatencySummary{
+		Count: h.total,
+		P50:   h.quantile(0.50),
+		P95:   h.quantile(0.95),
+		P99:   h.quantile(0.99),
+	}
+}
+
+// quantile returns the upper bound of the bucket holding quantile q.
+// Must be called with h.mu held.
+func (h *histogram) quantile(q float64) time.Duration {
+	if h.total == 0 {
+		return 0
+	}
+	rank := uint64(q*float64(h.total-1)) + 1
+	var seen uint64
+	bound := firstBucket
+	for b := 0; b < numBuckets; b++ {
+		seen += h.counts[b]
+		if seen >= rank {
+			break
+		}
+		bound *= 2
+	}
+	return bound
+}
+
+// observe records a block I/O on tier that began at start.
+func (s *Store) observe(tier string, write bool, start time.Time) {
+	p := pathLocalRead
+	switch {
+	case tier == "local" && write:
+		p = pathLocalWrite
+	case tier == "remote" && !write:
+		p = pathRemoteRead
+	case tier == "remote" && write:
+		p = pathRemoteWrite
+	}
+	s.latency[p].observe(time.Since(start))
+}
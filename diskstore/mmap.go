@@ -0,0 +1,32 @@
+package diskstore
+
+import (
+	"fmt"
+	"os"
+	"runtime/debug"
+)
+
+var pageSize = int64(os.Getpagesize())
+
+// pageSpan returns the page-aligned part of m covering [off, off+n), for
+// madvise hints on a single extent.
+func pageSpan(m []byte, off, n int64) []byte {
+	start := off &^ (pageSize - 1)
+	end := min(off+n, int64(len(m)))
+	return m[start:end]
+}
+
+// copyMapped copies src out of a file mapping. A media error on the backing
+// file surfaces as a fault while the pages are touched; it comes back as an
+// error instead of killing the process.
+func copyMapped(src []byte) (buf []byte, err error) {
+	defer debug.SetPanicOnFault(debug.SetPanicOnFault(true))
+	defer func() {
+		if r := recover(); r != nil {
+			buf, err = nil, fmt.Errorf("diskstore: fault reading mapped slab: %v", r)
+		}
+	}()
+	buf = make([]byte, len(src))
+	copy(buf, src)
+	return buf, nil
+}
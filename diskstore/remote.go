@@ -0,0 +1,181 @@
+package diskstore
+
+import (
+	"errors"
+	"fmt"
+	"sort"
+	"sync"
+	"time"
+)
+
+// ErrRemoteUnavailable is returned for operations on a remote mount while
+// it is marked down after repeated failures.
+var ErrRemoteUnavailable = errors.New("diskstore: remote tier unavailable")
+
+const (
+	defaultRemoteTimeout = 10 * time.Second
+	remoteBackoff        = 100 * time.Millisecond // doubled after each retry
+
+	// After breakerThreshold consecutive failed operations the remote tier
+	// is skipped for breakerCooldown before being tried again.
+	breakerThreshold = 3
+	breakerCooldown  = 30 * time.Second
+)
+
+// remoteMount is one remote tier directory.
+type remoteMount struct {
+	path   string
+	weight float64
+	budget int64 // this mount's share of remoteBudget
+	used   int64 // guarded by s.mu
+	health breaker
+
+	slabMu  sync.Mutex
+	slabs   *slabSet   // nil when each block has its own file; guarded by slabMu
+	opening sync.Mutex // serializes tierSlabs opening slabs late
+}
+
+// placement returns the mounts that can take n more bytes of block key,
+// least full relative to their budget first, so blocks spread in proportion
+// to the budget shares. Mounts marked down, and ones where the key's path is
+// busy, are skipped. Must be called with s.mu held.
+func (s *Store) placement(key BlockKey, n int64) []int {
+	now := time.Now()
+	var idx []int
+	for i, m := range s.remotes {
+		if m.budget > 0 && m.used+n <= m.budget && m.health.allow(now) &&
+			!s.busy[s.blockPath(key, "remote", i)] {
+			idx = append(idx, i)
+		}
+	}
+	fill := func(i int) float64 {
+		m := s.remotes[i]
+		return float64(m.used+n) / float64(m.budget)
+	}
+	sort.SliceStable(idx, func(a, b int) bool { return fill(idx[a]) < fill(idx[b]) })
+	return idx
+}
+
+// breaker tracks remote tier health. It has its own lock because remote
+// I/O runs without s.mu held.
+type breaker struct {
+	mu        sync.Mutex
+	failures  int
+	openUntil time.Time
+	hung      int // timed-out operations that have not returned yet
+}
+
+// allow reports whether the mount may be used. A mount with a timed-out
+// operation still running is held back until it returns, so no new attempt
+// races with it on the same files or slab extents.
+func (b *breaker) allow(now time.Time) bool {
+	b.mu.Lock()
+	defer b.mu.Unlock()
+	return b.hung == 0 && !now.Before(b.openUntil)
+}
+
+// trip marks the mount down for breakerCooldown, as repeated failures do.
+func (b *breaker) trip(now time.Time) {
+	b.mu.Lock()
+	defer b.mu.Unlock()
+	b.failures = 0
+	b.openUntil = now.Add(breakerCooldown)
+}
+
+func (b *breaker) hang(delta int) {
+	b.mu.Lock()
+	b.hung += delta
+	b.mu.Unlock()
+}
+
+func (b *breaker) record(err error, now time.Time) {
+	b.mu.Lock()
+	defer b.mu.Unlock()
+	if err == nil {
+		b.failures = 0
+		return
+	}
+	b.failures++
+	if b.failures >= breakerThreshold {
+		b.openUntil = now.Add(breakerCooldown)
+		b.failures = 0
+	}
+}
+
+// remoteIO runs an operation on remote mount m with a per-attempt timeout
+// and exponential backoff between retries. A hung NFS call is abandoned
+// rather than waited on: the mount is held back until it returns, and its
+// result, if it succeeded, is passed to discard (nil when op leaves nothing
+// behind). Call it without s.mu held.
+func remoteIO[T any](s *Store, m *remoteMount, op func() (T, error), discard func(T)) (T, error) {
+	if !m.health.allow(time.Now()) {
+		var zero T
+		return zero, ErrRemoteUnavailable
+	}
+
+	backoff := remoteBackoff
+	var val T
+	var err error
+	for attempt := 0; attempt < max(s.remoteRetries, 0)+1; attempt++ {
+		if attempt > 0 {
+			if !m.health.allow(time.Now()) {
+				break // an abandoned attempt is still running
+			}
+			time.Sleep(backoff)
+			backoff *= 2
+		}
+		if val, err = withTimeout(m, s.remoteTimeout, op, discard); err == nil {
+			break
+		}
+	}
+	m.health.record(err, time.Now())
+	return val, err
+}
+
+type ioResult[T any] struct {
+	val T
+	err error
+}
+
+// withTimeout runs op on m, giving up on it after d. An abandoned op marks
+// m hung until it returns, then has its result discarded.
+func withTimeout[T any](m *remoteMount, d time.Duration, op func() (T, error), discard func(T)) (T, error) {
+	var mu sync.Mutex // orders the op's hand-off against abandonment
+	abandoned := false
+	done := make(chan ioResult[T], 1)
+	go func() {
+		val, err := op()
+		mu.Lock()
+		gone := abandoned
+		if !gone {
+			done <- ioResult[T]{val, err}
+		}
+		mu.Unlock()
+		if gone {
+			if err == nil && discard != nil {
+				discard(val)
+			}
+			m.health.hang(-1)
+		}
+	}()
+
+	timer := time.NewTimer(d)
+	defer timer.Stop()
+	select {
+	case r := <-done:
+		return r.val, r.err
+	case <-timer.C:
+	}
+
+	mu.Lock()
+	defer mu.Unlock()
+	select {
+	case r := <-done: // finished as the timer fired
+		return r.val, r.err
+	default:
+	}
+	abandoned = true
+	m.health.hang(1)
+	var zero T
+	return zero, fmt.Errorf("diskstore: remote I/O timed out after %v", d)
+}
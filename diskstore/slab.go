@@ -0,0 +1,314 @@
+package diskstore
+
+import (
+	"errors"
+	"fmt"
+	"io"
+	"os"
+	"path/filepath"
+	"sort"
+	"sync"
+)
+
+// Extent locates a block payload inside a slab file.
+type Extent struct {
+	Slab   int   `json:"slab"`
+	Offset int64 `json:"offset"`
+	Length int64 `json:"length"` // payload bytes; the reservation is rounded up to the slab alignment
+}
+
+var errBlockTooLarge = errors.New("diskstore: block larger than slab size")
+
+// slabSet stores the blocks of one tier in a few large preallocated files
+// instead of one file per block, keeping inode counts and NFS metadata
+// traffic flat. It has its own lock because Get reads without s.mu held.
+type slabSet struct {
+	dir        string
+	size       int64 // bytes per slab file
+	align      int64 // extent offset and reservation alignment
+	direct     bool
+	mmap       bool // serve reads from a read-only mapping of each slab
+	durability *syncer
+
+	mu    sync.RWMutex // read-held while copying from a mapping, so close cannot unmap it
+	files []*os.File
+	maps  [][]byte // per-slab mapping; nil entries read with ReadAt
+	free  []Extent // free spans, sorted by (Slab, Offset)
+}
+
+func slabPath(dir string, i int) string {
+	return filepath.Join(dir, fmt.Sprintf("slab-%04d.kvslab", i))
+}
+
+// openSlabs opens the slab files already in dir and rebuilds the free map
+// from the extents the index still references. Existing slabs keep the size
+// they were created with, whatever size is passed now.
+func openSlabs(dir string, size int64, direct, mmap bool, y *syncer, used []Extent) (*slabSet, error) {
+	ss := &slabSet{dir: dir, size: size, align: 8, direct: direct, mmap: mmap, durability: y}
+	if direct {
+		ss.align = directAlign
+	}
+
+	for i := 0; ; i++ {
+		f, err := ss.openFile(slabPath(dir, i), os.O_RDWR)
+		if errors.Is(err, os.ErrNotExist) {
+			break
+		}
+		var fi os.FileInfo
+		if err == nil {
+			if fi, err = f.Stat(); err != nil {
+				f.Close()
+			}
+		}
+		if err == nil && i == 0 {
+			ss.size = fi.Size()
+		} else if err == nil && fi.Size() != ss.size {
+			f.Close()
+			err = fmt.Errorf("diskstore: slab %s is %d bytes, want %d", f.Name(), fi.Size(), ss.size)
+		}
+		if err != nil {
+			ss.close()
+			return nil, err
+		}
+		ss.add(f)
+	}
+	if ss.size <= 0 || ss.size%ss.align != 0 {
+		ss.close()
+		return nil, fmt.Errorf("diskstore: slab size %d is not a positive multiple of %d", ss.size, ss.align)
+	}
+
+	sort.Slice(used, func(i, j int) bool {
+		if used[i].Slab != used[j].Slab {
+			return used[i].Slab < used[j].Slab
+		}
+		return used[i].Offset < used[j].Offset
+	})
+	end := make([]int64, len(ss.files)) // end of the last used span per slab
+	for _, e := range used {
+		if e.Slab < 0 || e.Slab >= len(ss.files) {
+			ss.close()
+			return nil, fmt.Errorf("diskstore: index references missing slab %d in %s", e.Slab, dir)
+		}
+		if e.Offset%ss.align != 0 {
+			ss.close()
+			return nil, fmt.Errorf("diskstore: extent at %d in slab %d is not %d-byte aligned", e.Offset, e.Slab, ss.align)
+		}
+		if e.Length < 0 || e.Offset < end[e.Slab] || e.Offset+ss.reserved(e.Length) > ss.size {
+			ss.close()
+			return nil, fmt.Errorf("diskstore: extent at %d+%d in slab %d overlaps another or lies outside the %d-byte slab",
+				e.Offset, e.Length, e.Slab, ss.size)
+		}
+		if e.Offset > end[e.Slab] {
+			ss.free = append(ss.free, Extent{Slab: e.Slab, Offset: end[e.Slab], Length: e.Offset - end[e.Slab]})
+		}
+		end[e.Slab] = e.Offset + ss.reserved(e.Length)
+	}
+	for i := range ss.files {
+		if end[i] < ss.size {
+			ss.free = append(ss.free, Extent{Slab: i, Offset: end[i], Length: ss.size - end[i]})
+		}
+	}
+	sort.Slice(ss.free, func(i, j int) bool {
+		if ss.free[i].Slab != ss.free[j].Slab {
+			return ss.free[i].Slab < ss.free[j].Slab
+		}
+		return ss.free[i].Offset < ss.free[j].Offset
+	})
+	return ss, nil
+}
+
+func (ss *slabSet) openFile(path string, flag int) (*os.File, error) {
+	if ss.direct {
+		return openDirect(path, flag, 0644)
+	}
+	return os.OpenFile(path, flag, 0644)
+}
+
+// add appends an open slab file, mapping it when mmap reads are enabled.
+// A slab that cannot be mapped is read with ReadAt instead.
+func (ss *slabSet) add(f *os.File) {
+	var m []byte
+	if ss.mmap {
+		var err error
+		if m, err = mmapFile(f, int(ss.size)); err == nil {
+			adviseRandom(m)
+		} else {
+			m = nil
+		}
+	}
+	ss.files = append(ss.files, f)
+	ss.maps = append(ss.maps, m)
+}
+
+// reserved returns the slab space taken by a payload of n bytes.
+func (ss *slabSet) reserved(n int64) int64 {
+	if n == 0 {
+		return ss.align
+	}
+	return (n + ss.align - 1) &^ (ss.align - 1)
+}
+
+// alloc reserves space for an n-byte payload, first-fit, adding a new slab
+// file when no free span is large enough.
+func (ss *slabSet) alloc(n int64) (Extent, error) {
+	need := ss.reserved(n)
+	if need > ss.size {
+		return Extent{}, errBlockTooLarge
+	}
+
+	ss.mu.Lock()
+	defer ss.mu.Unlock()
+
+	for i, f := range ss.free {
+		if f.Length < need {
+			continue
+		}
+		if f.Length == need {
+			ss.free = append(ss.free[:i], ss.free[i+1:]...)
+		} else {
+			ss.free[i].Offset += need
+			ss.free[i].Length -= need
+		}
+		return Extent{Slab: f.Slab, Offset: f.Offset, Length: n}, nil
+	}
+
+	// The directory is missing if its mount was offline when the store
+	// opened.
+	if err := os.MkdirAll(ss.dir, 0755); err != nil {
+		return Extent{}, err
+	}
+	i := len(ss.files)
+	path := slabPath(ss.dir, i)
+	f, err := ss.openFile(path, os.O_RDWR|os.O_CREATE)
+	if err != nil {
+		return Extent{}, err
+	}
+	err = preallocate(f, ss.size)
+	if err == nil {
+		err = ss.durability.committed(path)
+	}
+	if err != nil {
+		f.Close()
+		os.Remove(path)
+		return Extent{}, err
+	}
+	ss.add(f)
+	if need < ss.size {
+		ss.free = append(ss.free, Extent{Slab: i, Offset: need, Length: ss.size - need})
+	}
+	return Extent{Slab: i, Offset: 0, Length: n}, nil
+}
+
+// release returns an extent's space to the free map, merging it with
+// adjacent free spans.
+func (ss *slabSet) release(e Extent) {
+	span := Extent{Slab: e.Slab, Offset: e.Offset, Length: ss.reserved(e.Length)}
+
+	ss.mu.Lock()
+	defer ss.mu.Unlock()
+
+	i := sort.Search(len(ss.free), func(i int) bool {
+		f := ss.free[i]
+		return f.Slab > span.Slab || (f.Slab == span.Slab && f.Offset > span.Offset)
+	})
+	ss.free = append(ss.free, Extent{})
+	copy(ss.free[i+1:], ss.free[i:])
+	ss.free[i] = span
+
+	if next := i + 1; next < len(ss.free) && ss.free[next].Slab == span.Slab &&
+		span.Offset+span.Length == ss.free[next].Offset {
+		ss.free[i].Length += ss.free[next].Length
+		ss.free = append(ss.free[:next], ss.free[next+1:]...)
+	}
+	if prev := i - 1; prev >= 0 && ss.free[prev].Slab == span.Slab &&
+		ss.free[prev].Offset+ss.free[prev].Length == span.Offset {
+		ss.free[prev].Length += ss.free[i].Length
+		ss.free = append(ss.free[:i], ss.free[i+1:]...)
+	}
+}
+
+func (ss *slabSet) file(i int) (*os.File, []byte, error) {
+	ss.mu.RLock()
+	defer ss.mu.RUnlock()
+	if i < 0 || i >= len(ss.files) {
+		return nil, nil, fmt.Errorf("diskstore: no slab %d in %s", i, ss.dir)
+	}
+	return ss.files[i], ss.maps[i], nil
+}
+
+// write stores payload at e with a positioned write. Slabs are written in
+// place: e is not yet in the index, so a torn write is never visible.
+func (ss *slabSet) write(e Extent, payload []byte) error {
+	f, _, err := ss.file(e.Slab)
+	if err != nil {
+		return err
+	}
+	if ss.direct {
+		// Pad to the aligned reservation; the tail is never read back.
+		buf := alignedBuf(len(payload))
+		copy(buf, payload)
+		payload = buf[:cap(buf)]
+	}
+	if _, err := f.WriteAt(payload, e.Offset); err != nil {
+		return err
+	}
+	ss.durability.mark(f.Name())
+	return ss.durability.sync(f, e.Offset, int64(len(payload)))
+}
+
+// read loads the payload at e, from the slab's mapping if it has one and
+// with a positioned read otherwise.
+func (ss *slabSet) read(e Extent) ([]byte, error) {
+	if buf, ok, err := ss.readMapped(e); ok {
+		return buf, err
+	}
+	f, _, err := ss.file(e.Slab)
+	if err != nil {
+		return nil, err
+	}
+
+	var buf, full []byte
+	if ss.direct {
+		buf = alignedBuf(int(e.Length))
+		full = buf[:cap(buf)]
+	} else {
+		buf = make([]byte, e.Length)
+		full = buf
+	}
+	n, err := f.ReadAt(full, e.Offset)
+	if n < len(buf) {
+		if err == nil || err == io.EOF {
+			err = io.ErrUnexpectedEOF
+		}
+		return nil, err
+	}
+	return buf, nil
+}
+
+// readMapped copies the payload at e out of its slab's mapping, reporting
+// ok=false if the slab is not mapped. The copy runs under the read lock so
+// close cannot unmap the slab beneath it.
+func (ss *slabSet) readMapped(e Extent) (buf []byte, ok bool, err error) {
+	ss.mu.RLock()
+	defer ss.mu.RUnlock()
+	if e.Slab < 0 || e.Slab >= len(ss.maps) || ss.maps[e.Slab] == nil {
+		return nil, false, nil
+	}
+	m := ss.maps[e.Slab]
+	adviseWillNeed(pageSpan(m, e.Offset, e.Length))
+	// Copy out: the extent may be reused once the block moves on.
+	buf, err = copyMapped(m[e.Offset : e.Offset+e.Length])
+	return buf, true, err
+}
+
+func (ss *slabSet) close() {
+	ss.mu.Lock()
+	defer ss.mu.Unlock()
+	for i, f := range ss.files {
+		if ss.maps[i] != nil {
+			munmap(ss.maps[i])
+		}
+		f.Close()
+	}
+	ss.files, ss.maps = nil, nil
+}
@@ -8,16 +8,26 @@ package diskstore
 import (
 	"encoding/binary"
 	"encoding/json"
+	"errors"
 	"fmt"
+	"hash/crc32"
+	"io"
 	"os"
 	"path/filepath"
 	"sort"
 	"sync"
+	"sync/atomic"
 	"time"
 
 	"github.com/klauspost/compress/zstd"
 )
 
+// ErrChecksum is returned when a block read from disk does not match the
+// checksum recorded when it was written.
+var ErrChecksum = errors.New("diskstore: checksum mismatch")
+
+var crcTable = crc32.MakeTable(crc32.Castagnoli)
+
 // BlockKey uniquely identifies an evicted KV block.
 type BlockKey struct {
 	Seq       int   `json:"seq"`        // Sequence (slot) ID
@@ -43,9 +53,24 @@ type BlockMeta struct {
 	Shape      []int     `json:"shape"`        // original tensor shape
 	SizeBytes  int       `json:"size_bytes"`   // uncompressed size
 	Compressed bool      `json:"compressed"`
+	Checksum   uint32    `json:"checksum,omitempty"` // CRC32C of the on-disk payload (0 = unchecked)
 	Tier       string    `json:"tier"`         // "local" or "remote"
+	Mount      int       `json:"mount,omitempty"` // remote tier mount, by position in the configured list
+	DiskBytes  int64     `json:"disk_bytes,omitempty"` // on-disk payload size (0 in older indexes)
+	Extent     *Extent   `json:"extent,omitempty"` // location in the tier's slab files (nil = own file)
 	StoredAt   time.Time `json:"stored_at"`
 	AccessedAt time.Time `json:"accessed_at"`
+
+	moving bool // a demotion is copying it to the remote tier; guarded by s.mu
+}
+
+// diskBytes returns the space the block takes on its tier. Indexes written
+// before DiskBytes was recorded fall back to the uncompressed size.
+func (m *BlockMeta) diskBytes() int64 {
+	if m.DiskBytes > 0 {
+		return m.DiskBytes
+	}
+	return int64(m.SizeBytes)
 }
 
 // Store is the tiered disk-backed storage engine.
@@ -54,11 +79,15 @@ type Store struct {
 
 	// local is the fast tier (SSD/NVMe).
 	localPath string
-	// remote is the slow tier (NFS/HDD), optional.
-	remotePath string
+	// remotes are the slow tier's directories (NFS/HDD); empty to disable.
+	remotes []*remoteMount
 
 	// In-memory index of all stored blocks.
 	index map[string]*BlockMeta // keyed by BlockKey.String()
+	// detached holds index entries for remote mounts missing from the
+	// config. They are saved back untouched, so dropping a mount from the
+	// config for one run does not forget its blocks.
+	detached map[string]*BlockMeta
 
 	// Budget limits.
 	localBudget int64
@@ -70,25 +99,128 @@ type Store struct {
 	compress    bool
 	encoder     *zstd.Encoder
 	decoder     *zstd.Decoder
+
+	// directIO bypasses the page cache for local tier block files.
+	directIO bool
+	// mmapReads serves local tier slab reads from mapped pages instead.
+	mmapReads bool
+
+	// durability applies the configured SyncPolicy to every write.
+	durability syncer
+
+	// localSlabs is nil when each local block has its own file.
+	localSlabs *slabSet
+	// slabSize is the configured SlabSize, 0 when slabs are off.
+	slabSize int64
+
+	// Remote tier fault handling.
+	remoteRetries int
+	remoteTimeout time.Duration
+
+	// unlinks holds remote block files dropped while s.mu was held, for
+	// unlock to delete once it is released. busy marks remote block paths
+	// with a copy or delete in flight, so no demotion writes one of them.
+	// Both are guarded by s.mu.
+	unlinks []unlink
+	busy    map[string]bool
+
+	// saveMu orders index saves, which run without s.mu held.
+	saveMu sync.Mutex
+
+	// checksumErrors counts payloads that failed verification on any hop.
+	checksumErrors atomic.Int64
+
+	// latency holds per-path block I/O histograms, indexed by path*.
+	latency [numPaths]histogram
+
+	// Idle expiry.
+	localIdle time.Duration
+	ttl       time.Duration
+	done      chan struct{}
+	sweeper   sync.WaitGroup // the expireLoop goroutine
+
+	closeOnce sync.Once
+	closeErr  error
+	closed    bool // guarded by mu; demotions in flight discard their copy
 }
 
 // Config for creating a new Store.
 type Config struct {
-	LocalPath    string // Path to local SSD storage directory.
-	RemotePath   string // Path to NFS/HDD storage directory (empty to disable).
-	LocalBudget  int64  // Max bytes on local tier.
-	RemoteBudget int64  // Max bytes on remote tier.
-	Compress     bool   // Apply zstd compression.
+	LocalPath    string        // Path to local SSD storage directory.
+	RemotePath   string        // Path to NFS/HDD storage directory (empty to disable).
+	LocalBudget  int64         // Max bytes on local tier (AutoBudget = size from free space).
+	RemoteBudget int64         // Max bytes on remote tier (AutoBudget = size from free space).
+	AutoHeadroom float64       // Fraction of each filesystem AutoBudget leaves free (0 = default of 0.1).
+	Compress     bool          // Apply zstd compression.
+	DirectIO     bool          // Use O_DIRECT for local tier block files.
+	MmapReads    bool          // Read local tier slabs through mmap (needs SlabSize, exclusive with DirectIO).
+	Durability   SyncPolicy    // When block writes are forced to disk (default SyncNone).
+	LocalIdle    time.Duration // Demote sequences idle this long to the remote tier (0 = never).
+	TTL          time.Duration // Drop sequences this long after they were first stored, however active (0 = never).
+	SlabSize     int64         // Store blocks in preallocated slab files of this size (0 = one file per block); existing slabs keep theirs.
+
+	RemoteMounts  []RemoteMount // Further remote tier directories sharing RemoteBudget with RemotePath.
+	RemoteRetries int           // Retries for failed remote I/O (0 or less = none).
+	RemoteTimeout time.Duration // Per-attempt remote I/O timeout (0 = default of 10s).
+}
+
+// RemoteMount is one remote tier directory. RemoteBudget is split across
+// mounts by weight and blocks are spread to keep them evenly full; a mount
+// that stops responding, or is unreachable when the store opens, is skipped
+// until it recovers. The index refers to mounts by position (RemotePath
+// first), so append new mounts at the end. Blocks on mounts left out of the
+// config stay in the index, unused, until the mount is configured again.
+type RemoteMount struct {
+	Path   string  `json:"path"`
+	Weight float64 `json:"weight"` // relative share of RemoteBudget (0 = 1)
 }
 
+// AutoBudget, as LocalBudget or RemoteBudget, sizes that tier when the store
+// opens: the blocks it already holds plus the free space on its filesystem,
+// less AutoHeadroom of the filesystem's total size.
+const AutoBudget = -1
+
+const defaultAutoHeadroom = 0.1
+
+// checkHeadroom rejects an AutoHeadroom outside [0, 1), NaN included.
+func checkHeadroom(h float64) error {
+	if !(h >= 0 && h < 1) {
+		return fmt.Errorf("diskstore: invalid auto headroom %v", h)
+	}
+	return nil
+}
+
+// expireInterval is how often the background sweep applies LocalIdle and TTL.
+const expireInterval = time.Minute
+
 // New creates a new tiered disk store.
 func New(cfg Config) (*Store, error) {
+	if cfg.DirectIO && cfg.MmapReads {
+		return nil, errors.New("diskstore: DirectIO and MmapReads are mutually exclusive")
+	}
+	if cfg.MmapReads && cfg.SlabSize <= 0 {
+		return nil, errors.New("diskstore: MmapReads requires SlabSize")
+	}
+	if err := checkHeadroom(cfg.AutoHeadroom); err != nil {
+		return nil, err
+	}
 	if err := os.MkdirAll(cfg.LocalPath, 0755); err != nil {
 		return nil, fmt.Errorf("diskstore: create local dir: %w", err)
 	}
+	mounts := cfg.RemoteMounts
 	if cfg.RemotePath != "" {
-		if err := os.MkdirAll(cfg.RemotePath, 0755); err != nil {
-			return nil, fmt.Errorf("diskstore: create remote dir: %w", err)
+		mounts = append([]RemoteMount{{Path: cfg.RemotePath}}, mounts...)
+	}
+	remotes := make([]*remoteMount, len(mounts))
+	for i, m := range mounts {
+		remotes[i] = &remoteMount{path: m.Path, weight: m.Weight}
+		if m.Weight <= 0 {
+			remotes[i].weight = 1
+		}
+		// An unreachable mount opens marked down rather than failing the
+		// store; the breaker lets it back in once it answers again.
+		if err := os.MkdirAll(m.Path, 0755); err != nil {
+			remotes[i].health.trip(time.Now())
 		}
 	}
 
@@ -108,45 +240,91 @@ func New(cfg Config) (*Store, error) {
 
 	s := &Store{
 		localPath:    cfg.LocalPath,
-		remotePath:   cfg.RemotePath,
+		remotes:      remotes,
 		index:        make(map[string]*BlockMeta),
+		detached:     make(map[string]*BlockMeta),
+		busy:         make(map[string]bool),
 		localBudget:  cfg.LocalBudget,
 		remoteBudget: cfg.RemoteBudget,
 		compress:     cfg.Compress,
+		directIO:     cfg.DirectIO,
+		mmapReads:    cfg.MmapReads,
+		durability:   syncer{policy: cfg.Durability},
 		encoder:      enc,
 		decoder:      dec,
+		localIdle:    cfg.LocalIdle,
+		ttl:          cfg.TTL,
+	}
+
+	s.remoteRetries = cfg.RemoteRetries
+	s.remoteTimeout = cfg.RemoteTimeout
+	if s.remoteTimeout <= 0 {
+		s.remoteTimeout = defaultRemoteTimeout
 	}
 
 	// Load existing index if present.
 	s.loadIndex()
 
+	if err := s.sizeTiers(cfg.AutoHeadroom); err != nil {
+		return nil, err
+	}
+
+	if cfg.SlabSize > 0 {
+		s.slabSize = cfg.SlabSize
+		if err := s.openSlabs(cfg.SlabSize); err != nil {
+			return nil, err
+		}
+	} else {
+		for _, meta := range s.index {
+			if meta.Extent != nil {
+				return nil, fmt.Errorf("diskstore: block %s is stored in a slab file; set SlabSize to open this store", meta.Key)
+			}
+		}
+	}
+
+	if s.localIdle > 0 || s.ttl > 0 {
+		s.done = make(chan struct{})
+		s.sweeper.Add(1)
+		go s.expireLoop()
+	}
+
 	return s, nil
 }
 
 // Put stores a KV tensor block to the local tier.
 func (s *Store) Put(key BlockKey, dtype string, shape []int, data []byte) error {
 	s.mu.Lock()
-	defer s.mu.Unlock()
+	defer s.unlock()
 
-	payload := data
-	compressed := false
-	if s.compress && s.encoder != nil {
-		payload = s.encoder.EncodeAll(data, nil)
-		compressed = true
+	// A re-Put replaces the old entry; drop it first so eviction below
+	// cannot migrate the file we are about to overwrite.
+	if old, ok := s.index[key.String()]; ok {
+		s.dropBlock(key.String(), old)
 	}
+	delete(s.detached, key.String())
 
-	// Check local budget; if full, evict oldest local blocks to remote.
-	for s.localUsed+int64(len(payload)) > s.localBudget {
-		if !s.evictLocalToRemote() {
-			break // no remote tier or remote is full
+	var ext *Extent
+	var size int64
+	var sum uint32
+	var err error
+	compressed := s.compress && s.encoder != nil
+	if compressed && !s.directIO && s.localSlabs == nil {
+		path := s.blockPath(key, "local", 0)
+		if err := os.MkdirAll(filepath.Dir(path), 0755); err != nil {
+			return err
 		}
+		size, sum, err = s.writeCompressed(path, data)
+	} else {
+		payload := data
+		if compressed {
+			// O_DIRECT writes need the whole payload in an aligned buffer,
+			// and slab extents need the final size up front.
+			payload = s.encoder.EncodeAll(data, nil)
+		}
+		size, sum = int64(len(payload)), crc32.Checksum(payload, crcTable)
+		ext, err = s.storeBlock("local", 0, key, payload)
 	}
-
-	path := s.blockPath(key, "local")
-	if err := os.MkdirAll(filepath.Dir(path), 0755); err != nil {
-		return err
-	}
-	if err := os.WriteFile(path, payload, 0644); err != nil {
+	if err != nil {
 		return err
 	}
 
@@ -156,12 +334,22 @@ func (s *Store) Put(key BlockKey, dtype string, shape []int, data []byte) error
 		Shape:      shape,
 		SizeBytes:  len(data),
 		Compressed: compressed,
+		Checksum:   sum,
 		Tier:       "local",
+		DiskBytes:  size,
+		Extent:     ext,
 		StoredAt:   time.Now(),
 		AccessedAt: time.Now(),
 	}
 	s.index[key.String()] = meta
-	s.localUsed += int64(len(payload))
+	s.localUsed += size
+
+	// Check local budget; if full, evict oldest local blocks to remote.
+	for s.localUsed > s.localBudget {
+		if !s.evictLocalToRemote(meta) {
+			break // no remote tier or remote is full
+		}
+	}
 
 	return nil
 }
@@ -171,14 +359,45 @@ func (s *Store) Put(key BlockKey, dtype string, shape []int, data []byte) error
 func (s *Store) Get(key BlockKey) ([]byte, *BlockMeta, error) {
 	s.mu.RLock()
 	meta, ok := s.index[key.String()]
+	var tier string
+	var mount int
+	var ext *Extent
+	if ok {
+		tier, mount, ext = meta.Tier, meta.Mount, meta.Extent
+	}
 	s.mu.RUnlock()
 
 	if !ok {
 		return nil, nil, nil
 	}
 
-	path := s.blockPath(key, meta.Tier)
-	payload, err := os.ReadFile(path)
+	read := func() ([]byte, error) {
+		if tier == "remote" {
+			return remoteIO(s, s.remotes[mount], func() ([]byte, error) {
+				return s.loadBlock("remote", mount, key, ext)
+			}, nil)
+		}
+		return s.loadBlock(tier, 0, key, ext)
+	}
+
+	// A mismatch on a block replaced or moved since the snapshot above is
+	// not corruption; start over from its new entry instead of counting it.
+	payload, err := read()
+	if err == nil && !intact(meta, payload) {
+		if s.moved(key.String(), meta, tier, mount, ext) {
+			return s.Get(key)
+		}
+		s.checksumErrors.Add(1)
+		// Re-issue once: a torn or truncated read often succeeds on retry.
+		payload, err = read()
+		if err == nil && !intact(meta, payload) {
+			if s.moved(key.String(), meta, tier, mount, ext) {
+				return s.Get(key)
+			}
+			s.checksumErrors.Add(1)
+			return nil, nil, fmt.Errorf("diskstore: block %s (%s tier): %w", key, tier, ErrChecksum)
+		}
+	}
 	if err != nil {
 		return nil, nil, fmt.Errorf("diskstore: read block %s: %w", key, err)
 	}
@@ -232,25 +451,32 @@ func (s *Store) GetRange(seq, layer int, isKey bool, beginPos, endPos int32) []B
 // RemoveSeq removes all blocks for a given sequence.
 func (s *Store) RemoveSeq(seq int) int {
 	s.mu.Lock()
-	defer s.mu.Unlock()
+	defer s.unlock()
 
 	var removed int
 	for k, meta := range s.index {
 		if meta.Key.Seq == seq {
-			path := s.blockPath(meta.Key, meta.Tier)
-			os.Remove(path)
-			if meta.Tier == "local" {
-				s.localUsed -= int64(meta.SizeBytes)
-			} else {
-				s.remoteUsed -= int64(meta.SizeBytes)
-			}
-			delete(s.index, k)
+			s.dropBlock(k, meta)
 			removed++
 		}
 	}
+	for k, meta := range s.detached {
+		if meta.Key.Seq == seq {
+			delete(s.detached, k)
+		}
+	}
 	return removed
 }
 
+// Expire applies the idle policy: sequences untouched for LocalIdle have
+// their local blocks demoted to the remote tier, and sequences first stored
+// more than TTL ago are removed entirely, however recently they were used.
+// Returns the number of blocks demoted and dropped. It runs periodically in
+// the background when either limit is set, but may also be called directly.
+func (s *Store) Expire() (demoted, dropped int) {
+	return s.expire(time.Now())
+}
+
 // Stats returns storage statistics.
 type Stats struct {
 	LocalBlocks  int   `json:"local_blocks"`
@@ -259,18 +485,58 @@ type Stats struct {
 	RemoteUsed   int64 `json:"remote_used"`
 	LocalBudget  int64 `json:"local_budget"`
 	RemoteBudget int64 `json:"remote_budget"`
+
+	// RemoteAvailable is false while the remote tier is disabled or marked
+	// down after repeated I/O failures.
+	RemoteAvailable bool `json:"remote_available"`
+
+	// ChecksumErrors counts block reads and remote copies that failed
+	// checksum verification, including ones that succeeded on retry.
+	ChecksumErrors int64 `json:"checksum_errors"`
+
+	// Latency summarizes block I/O time per path: local_read, local_write,
+	// remote_read and remote_write.
+	Latency map[string]LatencySummary `json:"latency"`
+
+	// Remotes breaks the remote tier down by mount.
+	Remotes []MountStats `json:"remotes,omitempty"`
+}
+
+// MountStats describes one remote tier directory.
+type MountStats struct {
+	Path      string `json:"path"`
+	Blocks    int    `json:"blocks"`
+	Used      int64  `json:"used"`
+	Budget    int64  `json:"budget"`
+	Available bool   `json:"available"`
+}
+
+// String summarizes tier usage with human-readable sizes.
+func (st Stats) String() string {
+	return fmt.Sprintf("local: %d blocks, %s of %s; remote: %d blocks, %s of %s",
+		st.LocalBlocks, FormatSize(st.LocalUsed), FormatSize(st.LocalBudget),
+		st.RemoteBlocks, FormatSize(st.RemoteUsed), FormatSize(st.RemoteBudget))
 }
 
 func (s *Store) Stats() Stats {
 	s.mu.RLock()
 	defer s.mu.RUnlock()
 
+	now := time.Now()
+	mounts := make([]MountStats, len(s.remotes))
+	available := false
+	for i, m := range s.remotes {
+		mounts[i] = MountStats{Path: m.path, Used: m.used, Budget: m.budget, Available: m.health.allow(now)}
+		available = available || mounts[i].Available
+	}
+
 	var local, remote int
 	for _, meta := range s.index {
 		if meta.Tier == "local" {
 			local++
 		} else {
 			remote++
+			mounts[meta.Mount].Blocks++
 		}
 	}
 
@@ -281,43 +547,455 @@ func (s *Store) Stats() Stats {
 		RemoteUsed:   s.remoteUsed,
 		LocalBudget:  s.localBudget,
 		RemoteBudget: s.remoteBudget,
+
+		RemoteAvailable: available,
+		ChecksumErrors:  s.checksumErrors.Load(),
+		Latency:         s.latencySummary(),
+		Remotes:         mounts,
 	}
 }
 
-// Close flushes the index and releases resources.
+func (s *Store) latencySummary() map[string]LatencySummary {
+	out := make(map[string]LatencySummary, numPaths)
+	for p := range s.latency {
+		out[pathNames[p]] = s.latency[p].summary()
+	}
+	return out
+}
+
+// Sync forces every block written so far to stable storage and saves the
+// index, so all blocks stored before it returns survive a crash. Under
+// SyncBatch this is the durability barrier; with SyncData it only adds the
+// index.
+func (s *Store) Sync() error {
+	return s.saveIndex()
+}
+
+// Close stops the expiry sweep, flushes the index and releases resources.
+// Later calls return the result of the first.
 func (s *Store) Close() error {
-	s.saveIndex()
+	s.closeOnce.Do(func() { s.closeErr = s.close() })
+	return s.closeErr
+}
+
+func (s *Store) close() error {
+	if s.done != nil {
+		close(s.done)
+		s.sweeper.Wait()
+	}
+	s.mu.Lock()
+	s.closed = true
+	s.mu.Unlock()
+	err := s.saveIndex()
+	s.mu.Lock()
+	if s.localSlabs != nil {
+		s.localSlabs.close()
+	}
+	for _, m := range s.remotes {
+		if m.slabs != nil {
+			m.slabs.close()
+		}
+	}
+	s.unlock()
 	if s.encoder != nil {
 		s.encoder.Close()
 	}
 	if s.decoder != nil {
 		s.decoder.Close()
 	}
-	return nil
+	return err
 }
 
 // ── internal ────────────────────────────────────────────────────────────────
 
-func (s *Store) blockPath(key BlockKey, tier string) string {
-	base := s.localPath
+// sizeTiers resolves AutoBudget for both tiers and splits the remote budget
+// across mounts: by weight when it was given, by each mount's own free space
+// when it is AutoBudget. Auto tiers on one filesystem split its free space
+// evenly. Must run after loadIndex, since blocks already on disk count
+// toward the budget but not free space.
+func (s *Store) sizeTiers(headroom float64) error {
+	if headroom <= 0 {
+		headroom = defaultAutoHeadroom
+	}
+	type autoTier struct {
+		budget *int64
+		path   string
+		used   int64
+		mount  *remoteMount // nil for the local tier
+		fs     string
+	}
+	var tiers []autoTier
+	if s.localBudget == AutoBudget {
+		tiers = append(tiers, autoTier{budget: &s.localBudget, path: s.localPath, used: s.localUsed})
+	}
+	remoteAuto := s.remoteBudget == AutoBudget
+	if remoteAuto {
+		for _, m := range s.remotes {
+			tiers = append(tiers, autoTier{budget: &m.budget, path: m.path, used: m.used, mount: m})
+		}
+	}
+
+	spare := make(map[string]int64) // free space above headroom, per filesystem
+	sharers := make(map[string]int64)
+	for i := range tiers {
+		t := &tiers[i]
+		fs, avail, total, err := diskSpace(t.path)
+		if err != nil {
+			if t.mount == nil || errors.Is(err, errors.ErrUnsupported) {
+				return fmt.Errorf("diskstore: size budget for %s: %w", t.path, err)
+			}
+			// An unreachable mount gets no budget until the store reopens.
+			t.mount.health.trip(time.Now())
+			t.budget = nil
+			continue
+		}
+		t.fs = fs
+		spare[fs] = avail - int64(headroom*float64(total))
+		sharers[fs]++
+	}
+	for _, t := range tiers {
+		if t.budget != nil {
+			*t.budget = max(t.used+spare[t.fs]/sharers[t.fs], 0)
+		}
+	}
+
+	if remoteAuto {
+		s.remoteBudget = 0
+		for _, m := range s.remotes {
+			s.remoteBudget += m.budget
+		}
+		return nil
+	}
+	var weights float64
+	for _, m := range s.remotes {
+		weights += m.weight
+	}
+	for _, m := range s.remotes {
+		m.budget = int64(float64(s.remoteBudget) * m.weight / weights)
+	}
+	return nil
+}
+
+// openSlabs opens the slab files of the local tier and every remote mount,
+// rebuilding their free space from the extents in the loaded index. O_DIRECT
+// and mmap apply to the local tier only.
+func (s *Store) openSlabs(size int64) error {
+	var local []Extent
+	remote := make([][]Extent, len(s.remotes))
+	for _, meta := range s.index {
+		switch {
+		case meta.Extent == nil:
+		case meta.Tier == "local":
+			local = append(local, *meta.Extent)
+		default:
+			remote[meta.Mount] = append(remote[meta.Mount], *meta.Extent)
+		}
+	}
+
+	var err error
+	if s.localSlabs, err = openSlabs(s.localPath, size, s.directIO, s.mmapReads, &s.durability, local); err != nil {
+		return fmt.Errorf("diskstore: open local slabs: %w", err)
+	}
+	now := time.Now()
+	for i, m := range s.remotes {
+		m.slabs, err = openSlabs(m.path, size, false, false, &s.durability, remote[i])
+		if err != nil && !m.health.allow(now) {
+			// The mount was unreachable at open; its slabs are opened once
+			// the breaker lets it back in. See tierSlabs.
+			continue
+		}
+		if err != nil {
+			s.localSlabs.close()
+			for _, prev := range s.remotes[:i] {
+				if prev.slabs != nil {
+					prev.slabs.close()
+				}
+			}
+			return fmt.Errorf("diskstore: open remote slabs: %w", err)
+		}
+	}
+	return nil
+}
+
+// tierDir returns the directory holding a tier's blocks; mount selects the
+// remote tier directory and is ignored for the local tier.
+func (s *Store) tierDir(tier string, mount int) string {
 	if tier == "remote" {
-		base = s.remotePath
+		return s.remotes[mount].path
 	}
+	return s.localPath
+}
+
+func (s *Store) blockPath(key BlockKey, tier string, mount int) string {
+	base := s.tierDir(tier, mount)
 	shard := key.Seq % 256
 	return filepath.Join(base, fmt.Sprintf("%02x", shard), key.String()+".kvblk")
 }
 
-// evictLocalToRemote moves the oldest local block to remote tier.
-// Must be called with s.mu held.
-func (s *Store) evictLocalToRemote() bool {
-	if s.remotePath == "" {
+// writeBlock writes a block file on the given tier.
+func (s *Store) writeBlock(path, tier string, payload []byte) error {
+	start := time.Now()
+	err := writeFileAtomic(path, payload, s.directIO && tier == "local", &s.durability)
+	s.observe(tier, true, start)
+	return err
+}
+
+// writeCompressed streams data through the zstd encoder into a local block
+// file, so the compressed payload is never materialized in memory. Like
+// writeFileAtomic it writes a temporary file and renames it into place.
+// Returns the on-disk size and its CRC32C. Must be called with s.mu held,
+// since the streaming encoder is shared.
+func (s *Store) writeCompressed(path string, data []byte) (int64, uint32, error) {
+	start := time.Now()
+	defer func() { s.latency[pathLocalWrite].observe(time.Since(start)) }()
+
+	tmp := path + ".tmp"
+	f, err := os.OpenFile(tmp, os.O_WRONLY|os.O_CREATE|os.O_TRUNC, 0644)
+	if err != nil {
+		return 0, 0, err
+	}
+	crc := crc32.New(crcTable)
+	cw := &countingWriter{w: io.MultiWriter(f, crc)}
+
+	s.encoder.Reset(cw)
+	_, werr := s.encoder.Write(data)
+	if cerr := s.encoder.Close(); werr == nil {
+		werr = cerr
+	}
+	if werr == nil {
+		werr = s.durability.sync(f, 0, cw.n)
+	}
+	if cerr := f.Close(); werr == nil {
+		werr = cerr
+	}
+	if werr == nil {
+		werr = os.Rename(tmp, path)
+	}
+	if werr != nil {
+		os.Remove(tmp)
+		return 0, 0, werr
+	}
+	return cw.n, crc.Sum32(), s.durability.committed(path)
+}
+
+type countingWriter struct {
+	w io.Writer
+	n int64
+}
+
+func (c *countingWriter) Write(p []byte) (int, error) {
+	n, err := c.w.Write(p)
+	c.n += int64(n)
+	return n, err
+}
+
+// readBlock reads a block file from the given tier.
+func (s *Store) readBlock(path, tier string) ([]byte, error) {
+	start := time.Now()
+	var data []byte
+	var err error
+	switch {
+	case s.directIO && tier == "local":
+		data, err = readFileDirect(path)
+	default:
+		data, err = os.ReadFile(path)
+	}
+	s.observe(tier, false, start)
+	return data, err
+}
+
+// slabs returns a tier's slab set, nil when each block has its own file or
+// the remote mount was offline at open and has not been reached since.
+func (s *Store) slabs(tier string, mount int) *slabSet {
+	if tier == "remote" {
+		m := s.remotes[mount]
+		m.slabMu.Lock()
+		defer m.slabMu.Unlock()
+		return m.slabs
+	}
+	return s.localSlabs
+}
+
+// tierSlabs is slabs for block I/O. With slabs enabled, it opens those of a
+// remote mount that was offline at open, failing with ErrRemoteUnavailable
+// while it cannot. Call it from a remoteIO op for the remote tier, without
+// s.mu held.
+func (s *Store) tierSlabs(tier string, mount int) (*slabSet, error) {
+	ss := s.slabs(tier, mount)
+	if ss != nil || tier != "remote" || s.slabSize == 0 {
+		return ss, nil
+	}
+
+	m := s.remotes[mount]
+	m.opening.Lock()
+	defer m.opening.Unlock()
+	if ss := s.slabs(tier, mount); ss != nil {
+		return ss, nil // opened by a concurrent call
+	}
+	s.mu.RLock()
+	used := s.mountExtents(mount)
+	s.mu.RUnlock()
+	ss, err := openSlabs(m.path, s.slabSize, false, false, &s.durability, used)
+	if err != nil {
+		return nil, fmt.Errorf("%w: open slabs in %s: %v", ErrRemoteUnavailable, m.path, err)
+	}
+
+	s.mu.Lock()
+	defer s.mu.Unlock()
+	if s.closed {
+		ss.close()
+		return nil, ErrRemoteUnavailable
+	}
+	// Blocks dropped while the slabs were opening released nothing.
+	live := make(map[Extent]bool)
+	for _, e := range s.mountExtents(mount) {
+		live[e] = true
+	}
+	for _, e := range used {
+		if !live[e] {
+			ss.release(e)
+		}
+	}
+	m.slabMu.Lock()
+	m.slabs = ss
+	m.slabMu.Unlock()
+	return ss, nil
+}
+
+// mountExtents returns the slab extents the index references on remote
+// mount i. Must be called with s.mu held.
+func (s *Store) mountExtents(i int) []Extent {
+	var used []Extent
+	for _, meta := range s.index {
+		if meta.Tier == "remote" && meta.Mount == i && meta.Extent != nil {
+			used = append(used, *meta.Extent)
+		}
+	}
+	return used
+}
+
+// storeBlock writes a new block payload on the given tier: into a freshly
+// allocated slab extent when slabs are enabled, else into its own file.
+func (s *Store) storeBlock(tier string, mount int, key BlockKey, payload []byte) (*Extent, error) {
+	ss, err := s.tierSlabs(tier, mount)
+	if err != nil {
+		return nil, err
+	}
+	if ss == nil {
+		path := s.blockPath(key, tier, mount)
+		if err := os.MkdirAll(filepath.Dir(path), 0755); err != nil {
+			return nil, err
+		}
+		return nil, s.writeBlock(path, tier, payload)
+	}
+
+	e, err := ss.alloc(int64(len(payload)))
+	if err != nil {
+		return nil, err
+	}
+	start := time.Now()
+	err = ss.write(e, payload)
+	s.observe(tier, true, start)
+	if err != nil {
+		ss.release(e)
+		return nil, err
+	}
+	return &e, nil
+}
+
+// loadBlock reads a block payload from the given tier.
+func (s *Store) loadBlock(tier string, mount int, key BlockKey, ext *Extent) ([]byte, error) {
+	if ext == nil {
+		return s.readBlock(s.blockPath(key, tier, mount), tier)
+	}
+	ss, err := s.tierSlabs(tier, mount)
+	if err != nil {
+		return nil, err
+	}
+	start := time.Now()
+	data, err := ss.read(*ext)
+	s.observe(tier, false, start)
+	return data, err
+}
+
+// removeBlock frees a block's storage on the given tier. An extent on a
+// mount whose slabs are not open yet needs no release: tierSlabs rebuilds
+// the free map from the index.
+func (s *Store) removeBlock(tier string, mount int, key BlockKey, ext *Extent) {
+	if ext != nil {
+		if ss := s.slabs(tier, mount); ss != nil {
+			ss.release(*ext)
+		}
+		return
+	}
+	os.Remove(s.blockPath(key, tier, mount))
+}
+
+// unlink is a remote block file queued for deletion.
+type unlink struct {
+	mount int
+	path  string
+}
+
+// releaseBlock frees a block's storage like removeBlock, but must be called
+// with s.mu held: a remote block file is queued for unlock to delete rather
+// than removed under the lock, where a hung mount would stall the store.
+func (s *Store) releaseBlock(tier string, mount int, key BlockKey, ext *Extent) {
+	if tier == "local" || ext != nil {
+		s.removeBlock(tier, mount, key, ext)
+		return
+	}
+	path := s.blockPath(key, tier, mount)
+	s.busy[path] = true
+	s.unlinks = append(s.unlinks, unlink{mount, path})
+}
+
+// unlock releases s.mu, then deletes the remote block files queued while it
+// was held through remoteIO. Files on a mount that is down stay queued for
+// a later unlock.
+func (s *Store) unlock() {
+	unlinks := s.unlinks
+	s.unlinks = nil
+	s.mu.Unlock()
+	if len(unlinks) == 0 {
+		return
+	}
+
+	var failed []unlink
+	for _, u := range unlinks {
+		_, err := remoteIO(s, s.remotes[u.mount], func() (struct{}, error) {
+			if err := os.Remove(u.path); err != nil && !errors.Is(err, os.ErrNotExist) {
+				return struct{}{}, err
+			}
+			return struct{}{}, nil
+		}, nil)
+		if err != nil {
+			failed = append(failed, u)
+		}
+	}
+
+	s.mu.Lock()
+	for _, u := range unlinks {
+		delete(s.busy, u.path)
+	}
+	for _, u := range failed {
+		s.busy[u.path] = true
+	}
+	s.unlinks = append(s.unlinks, failed...)
+	s.mu.Unlock()
+}
+
+// evictLocalToRemote moves the oldest local block other than keep to the
+// remote tier. Must be called with s.mu held; see moveToRemote.
+func (s *Store) evictLocalToRemote(keep *BlockMeta) bool {
+	if len(s.remotes) == 0 {
 		return false
 	}
 
 	// Find oldest local block.
 	var oldest *BlockMeta
 	for _, meta := range s.index {
-		if meta.Tier == "local" {
+		if meta.Tier == "local" && !meta.moving && meta != keep {
 			if oldest == nil || meta.AccessedAt.Before(oldest.AccessedAt) {
 				oldest = meta
 			}
@@ -327,44 +1005,231 @@ func (s *Store) evictLocalToRemote() bool {
 		return false
 	}
 
-	// Check remote budget.
-	if s.remoteUsed+int64(oldest.SizeBytes) > s.remoteBudget {
+	return s.moveToRemote(oldest)
+}
+
+// moveToRemote migrates a single local block to the remote tier, trying
+// each mount with room in placement order until one takes it. It must be
+// called with s.mu held but releases it around the remote I/O, so a slow
+// mount cannot stall Put and Get. The local copy is retired only if the
+// block was neither dropped nor replaced in the meantime.
+func (s *Store) moveToRemote(meta *BlockMeta) bool {
+	if meta.moving {
+		return false
+	}
+	// Verify the local read like Get does, re-reading once. A block that
+	// still fails would fail every Get too, so drop it rather than copy it.
+	k := meta.Key.String()
+	data, err := s.loadBlock("local", 0, meta.Key, meta.Extent)
+	if err == nil && !s.verify(meta, data) {
+		data, err = s.loadBlock("local", 0, meta.Key, meta.Extent)
+		if err == nil && !s.verify(meta, data) {
+			s.dropBlock(k, meta)
+			return false
+		}
+	}
+	if err != nil {
 		return false
 	}
+	n := int64(len(data))
+	sum := meta.Checksum
+	if sum == 0 {
+		sum = crc32.Checksum(data, crcTable) // unchecked block: match what was read
+	}
 
-	srcPath := s.blockPath(oldest.Key, "local")
-	dstPath := s.blockPath(oldest.Key, "remote")
+	meta.moving = true
+	defer func() { meta.moving = false }()
+	for _, i := range s.placement(meta.Key, n) {
+		m := s.remotes[i]
+		path := s.blockPath(meta.Key, "remote", i)
+		if m.used+n > m.budget || s.busy[path] {
+			continue // taken by a concurrent demotion
+		}
+		// Reserve the space and the path for as long as s.mu is released.
+		m.used += n
+		s.remoteUsed += n
+		s.busy[path] = true
+		s.mu.Unlock()
+		ext, err := s.copyToRemote(i, meta.Key, data, sum)
+		s.mu.Lock()
+		delete(s.busy, path)
 
-	if err := os.MkdirAll(filepath.Dir(dstPath), 0755); err != nil {
-		return false
+		current := !s.closed && s.index[k] == meta
+		if err != nil || !current {
+			m.used -= n
+			s.remoteUsed -= n
+			if !current {
+				if err == nil {
+					s.releaseBlock("remote", i, meta.Key, ext)
+				}
+				return false
+			}
+			continue // try the next mount
+		}
+		s.removeBlock("local", 0, meta.Key, meta.Extent)
+
+		s.localUsed -= meta.diskBytes()
+		meta.Tier = "remote"
+		meta.Mount = i
+		meta.DiskBytes = n
+		meta.Extent = ext
+		return true
 	}
+	return false
+}
 
-	data, err := os.ReadFile(srcPath)
-	if err != nil {
-		return false
+// copyToRemote writes a block payload to remote mount i and reads it back
+// before the local copy is deleted, to catch silent truncation; a mismatch
+// fails the attempt so remoteIO re-issues it. Runs without s.mu held.
+//
+// The read-back goes through the client page cache, so on NFS it catches
+// short or failed writes on this host but not truncation on the server.
+func (s *Store) copyToRemote(i int, key BlockKey, data []byte, sum uint32) (*Extent, error) {
+	return remoteIO(s, s.remotes[i], func() (*Extent, error) {
+		ext, err := s.storeBlock("remote", i, key, data)
+		if err != nil {
+			return nil, err
+		}
+		written, err := s.loadBlock("remote", i, key, ext)
+		if err == nil && crc32.Checksum(written, crcTable) != sum {
+			s.checksumErrors.Add(1)
+			err = fmt.Errorf("diskstore: remote copy of %s: %w", key, ErrChecksum)
+		}
+		if err != nil {
+			s.removeBlock("remote", i, key, ext)
+			return nil, err
+		}
+		return ext, nil
+	}, func(ext *Extent) {
+		s.removeBlock("remote", i, key, ext)
+	})
+}
+
+// verify reports whether payload matches the checksum recorded in meta,
+// counting mismatches. Blocks without a recorded checksum always pass.
+func (s *Store) verify(meta *BlockMeta, payload []byte) bool {
+	if intact(meta, payload) {
+		return true
 	}
-	if err := os.WriteFile(dstPath, data, 0644); err != nil {
-		return false
+	s.checksumErrors.Add(1)
+	return false
+}
+
+// intact is verify without the count.
+func intact(meta *BlockMeta, payload []byte) bool {
+	return meta.Checksum == 0 || crc32.Checksum(payload, crcTable) == meta.Checksum
+}
+
+// moved reports whether block k has been dropped, replaced or moved since
+// a read without s.mu took tier, mount and ext from meta.
+func (s *Store) moved(k string, meta *BlockMeta, tier string, mount int, ext *Extent) bool {
+	s.mu.RLock()
+	defer s.mu.RUnlock()
+	return s.index[k] != meta || meta.Tier != tier || meta.Mount != mount || meta.Extent != ext
+}
+
+// dropBlock deletes a block's storage and index entry.
+// Must be called with s.mu held; see releaseBlock.
+func (s *Store) dropBlock(k string, meta *BlockMeta) {
+	s.releaseBlock(meta.Tier, meta.Mount, meta.Key, meta.Extent)
+	if meta.Tier == "local" {
+		s.localUsed -= meta.diskBytes()
+	} else {
+		s.remoteUsed -= meta.diskBytes()
+		s.remotes[meta.Mount].used -= meta.diskBytes()
+	}
+	delete(s.index, k)
+}
+
+func (s *Store) expireLoop() {
+	defer s.sweeper.Done()
+	ticker := time.NewTicker(expireInterval)
+	defer ticker.Stop()
+	for {
+		select {
+		case <-s.done:
+			return
+		case now := <-ticker.C:
+			s.expire(now)
+		}
+	}
+}
+
+// expire implements Expire relative to the given time.
+func (s *Store) expire(now time.Time) (demoted, dropped int) {
+	s.mu.Lock()
+	defer s.unlock()
+
+	// A sequence is as fresh as its most recently accessed block and as
+	// old as its first stored one.
+	lastAccess := make(map[int]time.Time)
+	firstStored := make(map[int]time.Time)
+	for _, meta := range s.index {
+		seq := meta.Key.Seq
+		if meta.AccessedAt.After(lastAccess[seq]) {
+			lastAccess[seq] = meta.AccessedAt
+		}
+		if t, ok := firstStored[seq]; !ok || meta.StoredAt.Before(t) {
+			firstStored[seq] = meta.StoredAt
+		}
 	}
-	os.Remove(srcPath)
 
-	s.localUsed -= int64(len(data))
-	s.remoteUsed += int64(len(data))
-	oldest.Tier = "remote"
+	var idle []*BlockMeta
+	for k, meta := range s.index {
+		switch {
+		case s.ttl > 0 && now.Sub(firstStored[meta.Key.Seq]) >= s.ttl:
+			s.dropBlock(k, meta)
+			dropped++
+		case s.localIdle > 0 && now.Sub(lastAccess[meta.Key.Seq]) >= s.localIdle &&
+			meta.Tier == "local" && len(s.remotes) > 0:
+			idle = append(idle, meta)
+		}
+	}
 
-	return true
+	// Each demotion releases s.mu, so skip blocks changed in the meantime.
+	for _, meta := range idle {
+		if s.index[meta.Key.String()] == meta && meta.Tier == "local" && s.moveToRemote(meta) {
+			demoted++
+		}
+	}
+	return demoted, dropped
 }
 
 func (s *Store) indexPath() string {
 	return filepath.Join(s.localPath, "index.json")
 }
 
-func (s *Store) saveIndex() {
-	data, err := json.MarshalIndent(s.index, "", "  ")
+// saveIndex atomically replaces the index with a snapshot taken under
+// s.mu. Under SyncBatch, the blocks it references are flushed first and the
+// index itself after, both with s.mu released so a slow remote fdatasync
+// cannot stall Put and Get. Call it without s.mu held.
+func (s *Store) saveIndex() error {
+	s.saveMu.Lock()
+	defer s.saveMu.Unlock()
+
+	s.mu.RLock()
+	index := s.index
+	if len(s.detached) > 0 {
+		index = make(map[string]*BlockMeta, len(s.index)+len(s.detached))
+		for k, meta := range s.detached {
+			index[k] = meta
+		}
+		for k, meta := range s.index {
+			index[k] = meta
+		}
+	}
+	data, err := json.MarshalIndent(index, "", "  ")
+	s.mu.RUnlock()
 	if err != nil {
-		return
+		return err
 	}
-	os.WriteFile(s.indexPath(), data, 0644)
+	if err := s.durability.flush(); err != nil {
+		return err
+	}
+	if err := writeFileAtomic(s.indexPath(), data, false, &s.durability); err != nil {
+		return err
+	}
+	return s.durability.flush()
 }
 
 func (s *Store) loadIndex() {
@@ -374,12 +1239,17 @@ func (s *Store) loadIndex() {
 	}
 	json.Unmarshal(data, &s.index)
 
-	// Recalculate usage.
-	for _, meta := range s.index {
-		if meta.Tier == "local" {
-			s.localUsed += int64(meta.SizeBytes)
-		} else {
-			s.remoteUsed += int64(meta.SizeBytes)
+	// Recalculate usage, setting aside blocks on mounts no longer configured.
+	for k, meta := range s.index {
+		switch {
+		case meta.Tier == "local":
+			s.localUsed += meta.diskBytes()
+		case meta.Mount < 0 || meta.Mount >= len(s.remotes):
+			s.detached[k] = meta
+			delete(s.index, k)
+		default:
+			s.remoteUsed += meta.diskBytes()
+			s.remotes[meta.Mount].used += meta.diskBytes()
 		}
 	}
 }
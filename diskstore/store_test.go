@@ -1,9 +1,16 @@
 package diskstore
 
 import (
+	"errors"
+	"fmt"
+	"math"
 	"os"
 	"path/filepath"
+	"reflect"
+	"sync"
+	"sync/atomic"
 	"testing"
+	"time"
 )
 
 func TestPutAndGet(t *testing.T) {
@@ -80,7 +87,7 @@ func TestPutAndGetCompressed(t *testing.T) {
 	}
 
 	// Verify on-disk size is smaller than original.
-	path := store.blockPath(key, "local")
+	path := store.blockPath(key, "local", 0)
 	fi, _ := os.Stat(path)
 	if fi.Size() >= int64(len(data)) {
 		t.Errorf("compressed file (%d) should be smaller than original (%d)", fi.Size(), len(data))
@@ -233,3 +240,930 @@ func TestIndexPersistence(t *testing.T) {
 		t.Error("index not persisted across close/reopen")
 	}
 }
+
+func TestExpire(t *testing.T) {
+	dir := t.TempDir()
+	store, err := New(Config{
+		LocalPath:    filepath.Join(dir, "local"),
+		RemotePath:   filepath.Join(dir, "remote"),
+		LocalBudget:  1024 * 1024,
+		RemoteBudget: 1024 * 1024,
+		LocalIdle:    10 * time.Minute,
+		TTL:          time.Hour,
+	})
+	if err != nil {
+		t.Fatalf("New: %v", err)
+	}
+	defer store.Close()
+	defer store.Close() // a second Close is harmless
+
+	// Seq 0 is fresh, seq 1 is idle past LocalIdle, and seq 2 is still in
+	// use but was stored longer than TTL ago.
+	now := time.Now()
+	seqs := []struct{ stored, idle time.Duration }{
+		{0, 0},
+		{20 * time.Minute, 20 * time.Minute},
+		{2 * time.Hour, 0},
+	}
+	for seq, age := range seqs {
+		for i := 0; i < 2; i++ {
+			key := BlockKey{Seq: seq, Layer: 0, BeginPos: int32(i), EndPos: int32(i + 1), IsKey: true}
+			store.Put(key, "f16", []int{128}, make([]byte, 100))
+			store.index[key.String()].StoredAt = now.Add(-age.stored)
+			store.index[key.String()].AccessedAt = now.Add(-age.idle)
+		}
+	}
+
+	demoted, dropped := store.expire(now)
+	if demoted != 2 {
+		t.Errorf("expire: demoted %d, want 2", demoted)
+	}
+	if dropped != 2 {
+		t.Errorf("expire: dropped %d, want 2", dropped)
+	}
+
+	stats := store.Stats()
+	if stats.LocalBlocks != 2 || stats.RemoteBlocks != 2 {
+		t.Errorf("expire: local=%d remote=%d, want 2 and 2", stats.LocalBlocks, stats.RemoteBlocks)
+	}
+	if store.Has(BlockKey{Seq: 2, Layer: 0, BeginPos: 0, EndPos: 1, IsKey: true}) {
+		t.Error("seq 2 block still present after TTL")
+	}
+
+	// Demoted blocks are still readable.
+	got, meta, err := store.Get(BlockKey{Seq: 1, Layer: 0, BeginPos: 1, EndPos: 2, IsKey: true})
+	if err != nil || got == nil {
+		t.Fatalf("Get demoted block: %v", err)
+	}
+	if meta.Tier != "remote" {
+		t.Errorf("demoted block tier=%q, want remote", meta.Tier)
+	}
+}
+
+func TestChecksumMismatch(t *testing.T) {
+	dir := t.TempDir()
+	store, err := New(Config{
+		LocalPath:    filepath.Join(dir, "local"),
+		RemotePath:   filepath.Join(dir, "remote"),
+		LocalBudget:  1024 * 1024,
+		RemoteBudget: 1024 * 1024,
+	})
+	if err != nil {
+		t.Fatalf("New: %v", err)
+	}
+	defer store.Close()
+
+	key := BlockKey{Seq: 0, Layer: 0, BeginPos: 0, EndPos: 1, IsKey: true}
+	data := make([]byte, 512)
+	for i := range data {
+		data[i] = byte(i)
+	}
+	if err := store.Put(key, "f16", []int{128}, data); err != nil {
+		t.Fatalf("Put: %v", err)
+	}
+
+	// Truncate the block file behind the store's back.
+	path := store.blockPath(key, "local", 0)
+	if err := os.WriteFile(path, data[:256], 0644); err != nil {
+		t.Fatalf("corrupt block: %v", err)
+	}
+
+	got, _, err := store.Get(key)
+	if !errors.Is(err, ErrChecksum) {
+		t.Fatalf("Get: err=%v, want ErrChecksum", err)
+	}
+	if got != nil {
+		t.Error("Get returned data for a corrupt block")
+	}
+
+	// The read is re-issued once before giving up; both attempts count.
+	if n := store.Stats().ChecksumErrors; n != 2 {
+		t.Errorf("ChecksumErrors=%d, want 2", n)
+	}
+
+	// Demotion verifies its local read the same way, and drops the block
+	// instead of copying the corruption to the remote tier.
+	store.mu.Lock()
+	moved := store.moveToRemote(store.index[key.String()])
+	store.mu.Unlock()
+	if moved || store.Has(key) {
+		t.Errorf("corrupt block: moved=%v, present=%v; want it dropped", moved, store.Has(key))
+	}
+	stats := store.Stats()
+	if stats.ChecksumErrors != 4 || stats.RemoteBlocks != 0 {
+		t.Errorf("ChecksumErrors=%d RemoteBlocks=%d, want 4 and 0", stats.ChecksumErrors, stats.RemoteBlocks)
+	}
+}
+
+func TestDirectIO(t *testing.T) {
+	dir := t.TempDir()
+	store, err := New(Config{
+		LocalPath:    filepath.Join(dir, "local"),
+		RemotePath:   filepath.Join(dir, "remote"),
+		LocalBudget:  6000,
+		RemoteBudget: 1024 * 1024,
+		DirectIO:     true,
+	})
+	if err != nil {
+		t.Fatalf("New: %v", err)
+	}
+	defer store.Close()
+
+	// Odd sizes exercise padding and truncation; the third Put pushes the
+	// first block through a direct read into the remote tier.
+	sizes := []int{1, 4097, 3000}
+	for i, n := range sizes {
+		key := BlockKey{Seq: 0, Layer: 0, BeginPos: int32(i), EndPos: int32(i + 1), IsKey: true}
+		data := make([]byte, n)
+		for j := range data {
+			data[j] = byte(j + i)
+		}
+		if err := store.Put(key, "f16", []int{n}, data); err != nil {
+			t.Fatalf("Put %d: %v", i, err)
+		}
+		fi, err := os.Stat(store.blockPath(key, "local", 0))
+		if err != nil {
+			t.Fatalf("stat block %d: %v", i, err)
+		}
+		if fi.Size() != int64(n) {
+			t.Fatalf("block %d on disk: %d bytes, want %d", i, fi.Size(), n)
+		}
+	}
+
+	for i, n := range sizes {
+		key := BlockKey{Seq: 0, Layer: 0, BeginPos: int32(i), EndPos: int32(i + 1), IsKey: true}
+		got, _, err := store.Get(key)
+		if err != nil {
+			t.Fatalf("Get %d: %v", i, err)
+		}
+		if len(got) != n {
+			t.Fatalf("Get %d: got %d bytes, want %d", i, len(got), n)
+		}
+		for j := range got {
+			if got[j] != byte(j+i) {
+				t.Fatalf("Get %d: byte %d mismatch", i, j)
+			}
+		}
+	}
+	if store.Stats().RemoteBlocks == 0 {
+		t.Error("expected a block migrated to the remote tier")
+	}
+}
+
+func TestRemoteCircuitBreaker(t *testing.T) {
+	dir := t.TempDir()
+	remote := filepath.Join(dir, "remote")
+	store, err := New(Config{
+		LocalPath:     filepath.Join(dir, "local"),
+		RemotePath:    remote,
+		LocalBudget:   3000,
+		RemoteBudget:  1024 * 1024,
+		RemoteRetries: -1,
+	})
+	if err != nil {
+		t.Fatalf("New: %v", err)
+	}
+	defer store.Close()
+
+	key := func(i int) BlockKey {
+		return BlockKey{Seq: 0, Layer: 0, BeginPos: int32(i), EndPos: int32(i + 1), IsKey: true}
+	}
+
+	// Blocks 0 and 1 migrate to the remote tier; block 2 stays local.
+	for i := 0; i < 3; i++ {
+		if err := store.Put(key(i), "f16", []int{128}, make([]byte, 2000)); err != nil {
+			t.Fatalf("Put %d: %v", i, err)
+		}
+	}
+	if !store.Stats().RemoteAvailable {
+		t.Fatal("remote tier should start available")
+	}
+
+	// Take the remote tier away: the directory becomes a plain file.
+	os.RemoveAll(remote)
+	if err := os.WriteFile(remote, nil, 0644); err != nil {
+		t.Fatalf("replace remote dir: %v", err)
+	}
+
+	// Two failed reads and one failed demotion trip the breaker.
+	for i := 0; i < 2; i++ {
+		if _, _, err := store.Get(key(i)); err == nil {
+			t.Fatalf("Get %d: expected error from broken remote", i)
+		}
+	}
+	if err := store.Put(key(3), "f16", []int{128}, make([]byte, 2000)); err != nil {
+		t.Fatalf("Put with remote down: %v", err)
+	}
+
+	if store.Stats().RemoteAvailable {
+		t.Error("remote tier should be marked unavailable")
+	}
+	if _, _, err := store.Get(key(0)); !errors.Is(err, ErrRemoteUnavailable) {
+		t.Errorf("Get while down: err=%v, want ErrRemoteUnavailable", err)
+	}
+
+	// Local blocks are unaffected.
+	if got, _, err := store.Get(key(3)); err != nil || got == nil {
+		t.Errorf("Get local block while remote down: %v", err)
+	}
+
+	// Dropping remote blocks while the tier is down queues their files
+	// instead of waiting on it; they are deleted once it comes back.
+	for i := 0; i < 2; i++ {
+		if err := store.Put(key(i), "f16", []int{128}, make([]byte, 10)); err != nil {
+			t.Fatalf("re-Put %d with remote down: %v", i, err)
+		}
+	}
+	store.mu.Lock()
+	queued := len(store.unlinks)
+	store.mu.Unlock()
+	if queued != 2 {
+		t.Errorf("%d remote deletes queued while down, want 2", queued)
+	}
+	os.Remove(remote)
+	if err := os.Mkdir(remote, 0755); err != nil {
+		t.Fatalf("restore remote dir: %v", err)
+	}
+	store.remotes[0].health.openUntil = time.Time{}
+	store.RemoveSeq(1)
+	store.mu.Lock()
+	queued, busy := len(store.unlinks), len(store.busy)
+	store.mu.Unlock()
+	if queued != 0 || busy != 0 {
+		t.Errorf("%d deletes queued, %d paths busy once back; want none", queued, busy)
+	}
+}
+
+func TestRemoteMounts(t *testing.T) {
+	// One block per slab, so a slab mount that goes away mid-run cannot
+	// take blocks into a slab it still has open.
+	for _, slabSize := range []int64{0, 2000} {
+		t.Run(fmt.Sprintf("SlabSize=%d", slabSize), func(t *testing.T) {
+			testRemoteMounts(t, slabSize)
+		})
+	}
+}
+
+func testRemoteMounts(t *testing.T, slabSize int64) {
+	dir := t.TempDir()
+	nas := func(i int) string { return filepath.Join(dir, fmt.Sprintf("nas%d", i)) }
+	cfg := Config{
+		LocalPath:     filepath.Join(dir, "local"),
+		RemoteMounts:  []RemoteMount{{Path: nas(0), Weight: 2}, {Path: nas(1)}, {Path: nas(2)}},
+		LocalBudget:   2000,
+		RemoteBudget:  40000,
+		RemoteRetries: -1,
+		SlabSize:      slabSize,
+	}
+	store, err := New(cfg)
+	if err != nil {
+		t.Fatalf("New: %v", err)
+	}
+	defer func() { store.Close() }()
+
+	key := func(i int) BlockKey {
+		return BlockKey{Seq: 0, Layer: 0, BeginPos: int32(i), EndPos: int32(i + 1), IsKey: true}
+	}
+	blocks := func() []int {
+		var n []int
+		for _, m := range store.Stats().Remotes {
+			n = append(n, m.Blocks)
+		}
+		return n
+	}
+
+	// Eight demotions spread 2:1:1 across the mounts.
+	for i := 0; i < 9; i++ {
+		if err := store.Put(key(i), "f16", []int{128}, make([]byte, 2000)); err != nil {
+			t.Fatalf("Put %d: %v", i, err)
+		}
+	}
+	if got, want := blocks(), []int{4, 2, 2}; !reflect.DeepEqual(got, want) {
+		t.Fatalf("blocks per mount = %v, want %v", got, want)
+	}
+	if b := store.Stats().Remotes[0].Budget; b != 20000 {
+		t.Errorf("mount 0 budget = %d, want 20000", b)
+	}
+
+	// Placement survives a reopen.
+	store.Close()
+	if store, err = New(cfg); err != nil {
+		t.Fatalf("reopen: %v", err)
+	}
+	if got, want := blocks(), []int{4, 2, 2}; !reflect.DeepEqual(got, want) {
+		t.Fatalf("after reopen, blocks per mount = %v, want %v", got, want)
+	}
+	for i := 0; i < 8; i++ {
+		if got, _, err := store.Get(key(i)); err != nil || len(got) != 2000 {
+			t.Fatalf("Get %d after reopen: len=%d err=%v", i, len(got), err)
+		}
+	}
+
+	// With one mount gone, demotions fail over to the others.
+	os.RemoveAll(nas(1))
+	if err := os.WriteFile(nas(1), nil, 0644); err != nil {
+		t.Fatalf("replace mount dir: %v", err)
+	}
+	for i := 9; i < 13; i++ {
+		if err := store.Put(key(i), "f16", []int{128}, make([]byte, 2000)); err != nil {
+			t.Fatalf("Put %d with a mount down: %v", i, err)
+		}
+	}
+	st := store.Stats()
+	if st.LocalBlocks != 1 || st.RemoteBlocks != 12 {
+		t.Errorf("local=%d remote=%d blocks, want 1 and 12", st.LocalBlocks, st.RemoteBlocks)
+	}
+	if st.Remotes[1].Blocks != 2 {
+		t.Errorf("broken mount holds %d blocks, want 2", st.Remotes[1].Blocks)
+	}
+	if !st.RemoteAvailable {
+		t.Error("remote tier should stay available while other mounts work")
+	}
+
+	// A mount unreachable at open is marked down instead of failing New.
+	store.Close()
+	if store, err = New(cfg); err != nil {
+		t.Fatalf("reopen with a mount down: %v", err)
+	}
+	st = store.Stats()
+	if !st.Remotes[0].Available || st.Remotes[1].Available {
+		t.Errorf("mounts available = %v, %v after reopen; want only mount 1 down",
+			st.Remotes[0].Available, st.Remotes[1].Available)
+	}
+
+	// Leaving a mount out of the config for a run does not forget its blocks.
+	onLast := st.Remotes[2].Blocks
+	store.Close()
+	short := cfg
+	short.RemoteMounts = cfg.RemoteMounts[:2]
+	if store, err = New(short); err != nil {
+		t.Fatalf("reopen without mount 2: %v", err)
+	}
+	if got := store.Stats().RemoteBlocks; got != 12-onLast {
+		t.Errorf("remote blocks without mount 2 = %d, want %d", got, 12-onLast)
+	}
+	store.Close()
+	if store, err = New(cfg); err != nil {
+		t.Fatalf("reopen with mount 2: %v", err)
+	}
+	if got := store.Stats().Remotes[2].Blocks; got != onLast {
+		t.Errorf("mount 2 holds %d blocks after it returns, want %d", got, onLast)
+	}
+
+	// Blocks on a mount offline at open are unavailable until it is back,
+	// then read normally, from slabs opened late when there are any.
+	store.Close()
+	away := nas(0) + ".away"
+	if err := os.Rename(nas(0), away); err != nil {
+		t.Fatalf("take mount 0 away: %v", err)
+	}
+	if err := os.WriteFile(nas(0), nil, 0644); err != nil {
+		t.Fatalf("replace mount dir: %v", err)
+	}
+	if store, err = New(cfg); err != nil {
+		t.Fatalf("reopen with mount 0 down: %v", err)
+	}
+	var onFirst []BlockKey
+	for _, meta := range store.index {
+		if meta.Tier == "remote" && meta.Mount == 0 {
+			onFirst = append(onFirst, meta.Key)
+		}
+	}
+	if len(onFirst) == 0 {
+		t.Fatal("no blocks on mount 0")
+	}
+	for _, k := range onFirst {
+		if _, _, err := store.Get(k); !errors.Is(err, ErrRemoteUnavailable) {
+			t.Fatalf("Get %s while mount 0 is down: err=%v, want ErrRemoteUnavailable", k, err)
+		}
+	}
+	os.Remove(nas(0))
+	if err := os.Rename(away, nas(0)); err != nil {
+		t.Fatalf("bring mount 0 back: %v", err)
+	}
+	store.remotes[0].health.openUntil = time.Time{}
+	for _, k := range onFirst {
+		if got, _, err := store.Get(k); err != nil || len(got) != 2000 {
+			t.Fatalf("Get %s once mount 0 is back: len=%d err=%v", k, len(got), err)
+		}
+	}
+	if slabSize > 0 && store.slabs("remote", 0) == nil {
+		t.Error("mount 0 slabs not opened once it is back")
+	}
+}
+
+func TestRemoteTimeout(t *testing.T) {
+	s := &Store{remoteRetries: 2, remoteTimeout: 20 * time.Millisecond}
+	m := &remoteMount{}
+
+	release := make(chan struct{})
+	discarded := make(chan int, 1)
+	var attempts atomic.Int32
+	op := func() (int, error) {
+		attempts.Add(1)
+		<-release
+		return 42, nil
+	}
+	_, err := remoteIO(s, m, op, func(v int) { discarded <- v })
+	if err == nil {
+		t.Fatal("expected a timeout")
+	}
+	// The hung attempt is not retried, and holds the mount back until it
+	// returns.
+	if n := attempts.Load(); n != 1 {
+		t.Errorf("attempts = %d, want 1 while the first is still running", n)
+	}
+	if m.health.allow(time.Now()) {
+		t.Error("mount should be held back while an abandoned attempt runs")
+	}
+
+	close(release)
+	select {
+	case v := <-discarded:
+		if v != 42 {
+			t.Errorf("discarded %d, want 42", v)
+		}
+	case <-time.After(time.Second):
+		t.Fatal("abandoned result was never discarded")
+	}
+	deadline := time.Now().Add(time.Second)
+	for !m.health.allow(time.Now()) {
+		if time.Now().After(deadline) {
+			t.Fatal("mount still held back after the abandoned attempt returned")
+		}
+		time.Sleep(time.Millisecond)
+	}
+}
+
+func TestLatencyHistogram(t *testing.T) {
+	var h histogram
+	for i := 0; i < 98; i++ {
+		h.observe(80 * time.Microsecond)
+	}
+	h.observe(30 * time.Millisecond)
+	h.observe(time.Minute) // beyond the last bucket
+
+	sum := h.summary()
+	if sum.Count != 100 {
+		t.Errorf("Count=%d, want 100", sum.Count)
+	}
+	if sum.P50 != 100*time.Microsecond {
+		t.Errorf("P50=%v, want 100µs", sum.P50)
+	}
+	if sum.P99 < 30*time.Millisecond || sum.P99 > 60*time.Millisecond {
+		t.Errorf("P99=%v, want the 30ms bucket", sum.P99)
+	}
+
+	// Store I/O feeds the per-path histograms.
+	dir := t.TempDir()
+	store, err := New(Config{
+		LocalPath:   filepath.Join(dir, "local"),
+		LocalBudget: 1024 * 1024,
+	})
+	if err != nil {
+		t.Fatalf("New: %v", err)
+	}
+	defer store.Close()
+
+	key := BlockKey{Seq: 0, Layer: 0, BeginPos: 0, EndPos: 1, IsKey: true}
+	store.Put(key, "f16", []int{128}, make([]byte, 256))
+	store.Get(key)
+	lat := store.Stats().Latency
+	if lat["local_write"].Count != 1 || lat["local_read"].Count != 1 {
+		t.Errorf("latency counts: write=%d read=%d, want 1 and 1",
+			lat["local_write"].Count, lat["local_read"].Count)
+	}
+	if lat["remote_read"].Count != 0 {
+		t.Errorf("remote_read count=%d, want 0", lat["remote_read"].Count)
+	}
+}
+
+func TestPutReplaceCompressed(t *testing.T) {
+	dir := t.TempDir()
+	store, err := New(Config{
+		LocalPath:   filepath.Join(dir, "local"),
+		LocalBudget: 1024 * 1024,
+		Compress:    true,
+	})
+	if err != nil {
+		t.Fatalf("New: %v", err)
+	}
+	defer store.Close()
+
+	key := BlockKey{Seq: 0, Layer: 0, BeginPos: 0, EndPos: 1, IsKey: true}
+	for _, fill := range []byte{1, 2} {
+		data := make([]byte, 4096)
+		for i := range data {
+			data[i] = fill
+		}
+		if err := store.Put(key, "f16", []int{128}, data); err != nil {
+			t.Fatalf("Put %d: %v", fill, err)
+		}
+	}
+
+	if n := store.Stats().LocalBlocks; n != 1 {
+		t.Errorf("LocalBlocks=%d after re-Put, want 1", n)
+	}
+	got, _, err := store.Get(key)
+	if err != nil {
+		t.Fatalf("Get: %v", err)
+	}
+	if len(got) != 4096 || got[0] != 2 || got[4095] != 2 {
+		t.Errorf("Get returned stale or short data (len=%d)", len(got))
+	}
+}
+
+func TestSlabStorage(t *testing.T) {
+	dir := t.TempDir()
+	cfg := Config{
+		LocalPath:    filepath.Join(dir, "local"),
+		RemotePath:   filepath.Join(dir, "remote"),
+		LocalBudget:  40000,
+		RemoteBudget: 1024 * 1024,
+		DirectIO:     true,
+		SlabSize:     64 * 1024,
+	}
+	store, err := New(cfg)
+	if err != nil {
+		t.Fatalf("New: %v", err)
+	}
+
+	blockData := func(i int) []byte {
+		data := make([]byte, 3000)
+		for j := range data {
+			data[j] = byte(i*7 + j)
+		}
+		return data
+	}
+	key := func(i int) BlockKey {
+		return BlockKey{Seq: i % 2, Layer: 0, BeginPos: int32(i), EndPos: int32(i + 1), IsKey: true}
+	}
+	check := func(s *Store, i int) {
+		t.Helper()
+		got, _, err := s.Get(key(i))
+		if err != nil {
+			t.Fatalf("Get %d: %v", i, err)
+		}
+		want := blockData(i)
+		if len(got) != len(want) {
+			t.Fatalf("Get %d: got %d bytes, want %d", i, len(got), len(want))
+		}
+		for j := range got {
+			if got[j] != want[j] {
+				t.Fatalf("Get %d: byte %d mismatch", i, j)
+			}
+		}
+	}
+
+	// 20 blocks overflow the local budget, so some migrate between slabs.
+	for i := 0; i < 20; i++ {
+		if err := store.Put(key(i), "f16", []int{3000}, blockData(i)); err != nil {
+			t.Fatalf("Put %d: %v", i, err)
+		}
+	}
+	if store.Stats().RemoteBlocks == 0 {
+		t.Fatal("expected blocks migrated to the remote tier")
+	}
+	for i := 0; i < 20; i++ {
+		check(store, i)
+	}
+	if files, _ := filepath.Glob(filepath.Join(dir, "*", "*", "*.kvblk")); len(files) != 0 {
+		t.Fatalf("found per-block files with slabs enabled: %v", files)
+	}
+
+	// Freed extents are reused rather than growing new slabs.
+	store.RemoveSeq(1)
+	for i := 21; i < 40; i += 2 {
+		if err := store.Put(key(i), "f16", []int{3000}, blockData(i)); err != nil {
+			t.Fatalf("Put %d: %v", i, err)
+		}
+	}
+	for _, tier := range []string{"local", "remote"} {
+		slabs, _ := filepath.Glob(filepath.Join(dir, tier, "*.kvslab"))
+		if len(slabs) != 1 {
+			t.Errorf("%s tier: %d slab files, want 1", tier, len(slabs))
+		}
+	}
+	store.Close()
+
+	// Reopening rebuilds the free map without handing out live extents.
+	store, err = New(cfg)
+	if err != nil {
+		t.Fatalf("reopen: %v", err)
+	}
+	if err := store.Put(key(40), "f16", []int{3000}, blockData(40)); err != nil {
+		t.Fatalf("Put after reopen: %v", err)
+	}
+	for i := 0; i <= 40; i += 2 {
+		check(store, i)
+	}
+	for i := 21; i < 40; i += 2 {
+		check(store, i)
+	}
+	store.Close()
+
+	// Existing slabs keep their on-disk size when SlabSize changes.
+	resized := cfg
+	resized.SlabSize = 16 * 1024
+	resized.DirectIO = false
+	resized.MmapReads = true
+	store, err = New(resized)
+	if err != nil {
+		t.Fatalf("reopen with smaller SlabSize: %v", err)
+	}
+	for i := 0; i <= 40; i += 2 {
+		check(store, i)
+	}
+	store.Close()
+
+	// Turning slabs off would strand the blocks stored in them.
+	unslabbed := cfg
+	unslabbed.SlabSize = 0
+	if store, err := New(unslabbed); err == nil {
+		store.Close()
+		t.Fatal("New without SlabSize accepted an index with slab extents")
+	}
+}
+
+func TestMmapReads(t *testing.T) {
+	if _, err := New(Config{LocalPath: t.TempDir(), DirectIO: true, MmapReads: true}); err == nil {
+		t.Fatal("expected DirectIO with MmapReads to be rejected")
+	}
+
+	if _, err := New(Config{LocalPath: t.TempDir(), MmapReads: true}); err == nil {
+		t.Fatal("expected MmapReads without SlabSize to be rejected")
+	}
+
+	for _, slabSize := range []int64{64 * 1024, 16 * 1024} {
+		store, err := New(Config{
+			LocalPath:   t.TempDir(),
+			LocalBudget: 1024 * 1024,
+			MmapReads:   true,
+			SlabSize:    slabSize,
+		})
+		if err != nil {
+			t.Fatalf("New (slab size %d): %v", slabSize, err)
+		}
+
+		sizes := []int{1, 4097, 3000}
+		for i, n := range sizes {
+			key := BlockKey{Seq: 0, Layer: 0, BeginPos: int32(i), EndPos: int32(i + 1), IsKey: true}
+			data := make([]byte, n)
+			for j := range data {
+				data[j] = byte(j + i)
+			}
+			if err := store.Put(key, "f16", []int{n}, data); err != nil {
+				t.Fatalf("Put %d (slab size %d): %v", i, slabSize, err)
+			}
+		}
+		for i, n := range sizes {
+			key := BlockKey{Seq: 0, Layer: 0, BeginPos: int32(i), EndPos: int32(i + 1), IsKey: true}
+			got, _, err := store.Get(key)
+			if err != nil {
+				t.Fatalf("Get %d (slab size %d): %v", i, slabSize, err)
+			}
+			if len(got) != n {
+				t.Fatalf("Get %d (slab size %d): got %d bytes, want %d", i, slabSize, len(got), n)
+			}
+			for j := range got {
+				if got[j] != byte(j+i) {
+					t.Fatalf("Get %d (slab size %d): byte %d mismatch", i, slabSize, j)
+				}
+			}
+		}
+
+		// Close waits for reads copying from a mapping instead of
+		// unmapping it beneath them.
+		var readers sync.WaitGroup
+		for r := 0; r < 4; r++ {
+			readers.Add(1)
+			go func() {
+				defer readers.Done()
+				key := BlockKey{Seq: 0, Layer: 0, BeginPos: 1, EndPos: 2, IsKey: true}
+				for {
+					if _, _, err := store.Get(key); err != nil {
+						return
+					}
+				}
+			}()
+		}
+		time.Sleep(10 * time.Millisecond)
+		store.Close()
+		readers.Wait()
+	}
+}
+
+func TestDurability(t *testing.T) {
+	for _, policy := range []SyncPolicy{SyncNone, SyncData, SyncBatch} {
+		for _, slabSize := range []int64{0, 64 * 1024} {
+			dir := t.TempDir()
+			cfg := Config{
+				LocalPath:    filepath.Join(dir, "local"),
+				RemotePath:   filepath.Join(dir, "remote"),
+				LocalBudget:  5000,
+				RemoteBudget: 1024 * 1024,
+				Compress:     true,
+				SlabSize:     slabSize,
+				Durability:   policy,
+			}
+			store, err := New(cfg)
+			if err != nil {
+				t.Fatalf("New (policy %d, slab size %d): %v", policy, slabSize, err)
+			}
+			for i := 0; i < 4; i++ {
+				key := BlockKey{Seq: 0, Layer: 0, BeginPos: int32(i), EndPos: int32(i + 1), IsKey: true}
+				data := make([]byte, 2048)
+				for j := range data {
+					data[j] = byte(j * (i + 1))
+				}
+				if err := store.Put(key, "f16", []int{2048}, data); err != nil {
+					t.Fatalf("Put %d (policy %d, slab size %d): %v", i, policy, slabSize, err)
+				}
+			}
+			if err := store.Sync(); err != nil {
+				t.Fatalf("Sync (policy %d, slab size %d): %v", policy, slabSize, err)
+			}
+
+			// Every write went through a rename; nothing temporary is left.
+			tmps, _ := filepath.Glob(filepath.Join(dir, "*", "*.tmp"))
+			nested, _ := filepath.Glob(filepath.Join(dir, "*", "*", "*.tmp"))
+			if len(tmps)+len(nested) != 0 {
+				t.Fatalf("leftover temp files (policy %d, slab size %d): %v %v", policy, slabSize, tmps, nested)
+			}
+
+			// Reopen without Close, as after a crash following Sync.
+			reopened, err := New(cfg)
+			if err != nil {
+				t.Fatalf("reopen (policy %d, slab size %d): %v", policy, slabSize, err)
+			}
+			for i := 0; i < 4; i++ {
+				key := BlockKey{Seq: 0, Layer: 0, BeginPos: int32(i), EndPos: int32(i + 1), IsKey: true}
+				got, _, err := reopened.Get(key)
+				if err != nil || len(got) != 2048 {
+					t.Fatalf("Get %d after reopen (policy %d, slab size %d): %d bytes, %v", i, policy, slabSize, len(got), err)
+				}
+				for j := range got {
+					if got[j] != byte(j*(i+1)) {
+						t.Fatalf("Get %d after reopen (policy %d, slab size %d): byte %d mismatch", i, policy, slabSize, j)
+					}
+				}
+			}
+			reopened.Close()
+			store.Close()
+		}
+	}
+}
+
+func TestLoadEnv(t *testing.T) {
+	t.Setenv("OLLAMA_KV_TIER_REMOTE", "/mnt/kv")
+	t.Setenv("OLLAMA_KV_TIER_LOCAL_GB", "2")
+	t.Setenv("OLLAMA_KV_TIER_COMPRESS", "1")
+	t.Setenv("OLLAMA_KV_TIER_DURABILITY", "batch")
+	t.Setenv("OLLAMA_KV_TIER_TTL", "24h")
+	t.Setenv("OLLAMA_KV_TIER_REMOTE_RETRIES", "-1")
+	t.Setenv("OLLAMA_KV_TIER_REMOTE_MOUNTS", "/mnt/nas1:2, /mnt/nas2")
+
+	cfg := Config{LocalPath: "/tmp/kv", RemoteBudget: 100}
+	if err := cfg.LoadEnv(); err != nil {
+		t.Fatalf("LoadEnv: %v", err)
+	}
+	want := Config{
+		LocalPath:     "/tmp/kv", // unset variables keep the base value
+		RemotePath:    "/mnt/kv",
+		LocalBudget:   2 << 30,
+		RemoteBudget:  100,
+		Compress:      true,
+		Durability:    SyncBatch,
+		TTL:           24 * time.Hour,
+		RemoteMounts:  []RemoteMount{{Path: "/mnt/nas1", Weight: 2}, {Path: "/mnt/nas2"}},
+		RemoteRetries: -1,
+	}
+	if !reflect.DeepEqual(cfg, want) {
+		t.Errorf("LoadEnv: got %+v, want %+v", cfg, want)
+	}
+
+	t.Setenv("OLLAMA_KV_TIER_LOCAL_IDLE", "soon")
+	if err := cfg.LoadEnv(); err == nil {
+		t.Error("expected an error for an unparsable duration")
+	}
+	os.Unsetenv("OLLAMA_KV_TIER_LOCAL_IDLE")
+	for _, bad := range []string{"NaN", "Inf", "-0.1", "1"} {
+		t.Setenv("OLLAMA_KV_TIER_AUTO_HEADROOM", bad)
+		if err := cfg.LoadEnv(); err == nil {
+			t.Errorf("AUTO_HEADROOM=%s: expected an error", bad)
+		}
+	}
+}
+
+func TestAutoBudget(t *testing.T) {
+	dir := t.TempDir()
+	store, err := New(Config{
+		LocalPath:    dir,
+		LocalBudget:  AutoBudget,
+		RemoteBudget: AutoBudget, // no remote tier: resolves to 0
+		AutoHeadroom: 1e-9,
+	})
+	if err != nil {
+		t.Fatalf("New: %v", err)
+	}
+	stats := store.Stats()
+	store.Close()
+	if stats.LocalBudget <= 0 {
+		t.Errorf("local budget %d, want free space on %s", stats.LocalBudget, dir)
+	}
+	if stats.RemoteBudget != 0 {
+		t.Errorf("remote budget %d without a remote tier, want 0", stats.RemoteBudget)
+	}
+
+	// Tiers on one filesystem split its free space instead of each
+	// claiming all of it.
+	store, err = New(Config{
+		LocalPath:    filepath.Join(dir, "local"),
+		LocalBudget:  AutoBudget,
+		RemoteMounts: []RemoteMount{{Path: filepath.Join(dir, "a")}, {Path: filepath.Join(dir, "b")}},
+		RemoteBudget: AutoBudget,
+		AutoHeadroom: 1e-9,
+	})
+	if err != nil {
+		t.Fatalf("New with shared filesystem: %v", err)
+	}
+	shared := store.Stats()
+	store.Close()
+	if shared.RemoteBudget <= 0 {
+		t.Errorf("remote budget %d, want a share of the free space", shared.RemoteBudget)
+	}
+	if sum := shared.LocalBudget + shared.RemoteBudget; sum > stats.LocalBudget+stats.LocalBudget/2 {
+		t.Errorf("local %d + remote %d budgets exceed the %d bytes free", shared.LocalBudget, shared.RemoteBudget, stats.LocalBudget)
+	}
+
+	// Headroom is a fraction of the filesystem, short of all of it.
+	for _, bad := range []float64{math.NaN(), math.Inf(1), -0.1, 1} {
+		if _, err := New(Config{LocalPath: dir, LocalBudget: AutoBudget, AutoHeadroom: bad}); err == nil {
+			t.Errorf("AutoHeadroom %v: expected an error", bad)
+		}
+	}
+
+	// Reserving nearly the whole filesystem as headroom leaves nothing.
+	store, err = New(Config{LocalPath: dir, LocalBudget: AutoBudget, AutoHeadroom: 1 - 1e-9})
+	if err != nil {
+		t.Fatalf("New: %v", err)
+	}
+	defer store.Close()
+	if b := store.Stats().LocalBudget; b != 0 {
+		t.Errorf("local budget %d with full headroom, want 0", b)
+	}
+}
+
+func TestSizeUnits(t *testing.T) {
+	for _, tc := range []struct {
+		in   string
+		want int64
+	}{
+		{"1048576", 1 << 20},
+		{"8GiB", 8 << 30},
+		{"500MB", 500e6},
+		{"1.5 TiB", 3 << 39},
+		{"512B", 512},
+	} {
+		got, err := ParseSize(tc.in)
+		if err != nil || got != tc.want {
+			t.Errorf("ParseSize(%q) = %d, %v; want %d", tc.in, got, err, tc.want)
+		}
+	}
+	for _, bad := range []string{"", "GiB", "-1MiB", "8 gigs", "-5", "InfGiB", "NaN B", "1e30GiB", "9223372036854775808"} {
+		if _, err := ParseSize(bad); err == nil {
+			t.Errorf("ParseSize(%q): expected an error", bad)
+		}
+	}
+
+	for _, tc := range []struct {
+		in   int64
+		want string
+	}{
+		{0, "0 B"},
+		{1023, "1023 B"},
+		{1536, "1.5 KiB"},
+		{20 << 30, "20.0 GiB"},
+	} {
+		if got := FormatSize(tc.in); got != tc.want {
+			t.Errorf("FormatSize(%d) = %q, want %q", tc.in, got, tc.want)
+		}
+	}
+
+	// Budgets keep bare numbers as GiB but accept explicit units.
+	t.Setenv("OLLAMA_KV_TIER_LOCAL_GB", "500MiB")
+	t.Setenv("OLLAMA_KV_TIER_SLAB_SIZE", "1GiB")
+	var cfg Config
+	if err := cfg.LoadEnv(); err != nil {
+		t.Fatalf("LoadEnv: %v", err)
+	}
+	if cfg.LocalBudget != 500<<20 || cfg.SlabSize != 1<<30 {
+		t.Errorf("LoadEnv: local budget %d, slab size %d", cfg.LocalBudget, cfg.SlabSize)
+	}
+	for _, bad := range []string{"-5", "9000000000000"} {
+		t.Setenv("OLLAMA_KV_TIER_LOCAL_GB", bad)
+		if err := cfg.LoadEnv(); err == nil {
+			t.Errorf("LoadEnv: local budget %q accepted as %d", bad, cfg.LocalBudget)
+		}
+	}
+}
@@ -0,0 +1,82 @@
+//go:build linux
+
+package diskstore
+
+import (
+	"errors"
+	"fmt"
+	"os"
+	"syscall"
+)
+
+// openDirect opens path with O_DIRECT, falling back to buffered I/O on
+// filesystems that reject it (e.g. older tmpfs, some FUSE mounts).
+func openDirect(path string, flag int, perm os.FileMode) (*os.File, error) {
+	f, err := os.OpenFile(path, flag|syscall.O_DIRECT, perm)
+	if errors.Is(err, syscall.EINVAL) {
+		return os.OpenFile(path, flag, perm)
+	}
+	return f, err
+}
+
+// preallocate reserves size bytes of disk for f, so slab writes never fail
+// for lack of space mid-block. Filesystems without fallocate (NFSv3, some
+// FUSE mounts) get a sparse file instead.
+func preallocate(f *os.File, size int64) error {
+	if err := syscall.Fallocate(int(f.Fd()), 0, 0, size); err == nil {
+		return nil
+	}
+	return f.Truncate(size)
+}
+
+// mmapFile maps the first size bytes of f read-only and shared, so reads
+// come straight from the page cache.
+func mmapFile(f *os.File, size int) ([]byte, error) {
+	return syscall.Mmap(int(f.Fd()), 0, size, syscall.PROT_READ, syscall.MAP_SHARED)
+}
+
+func munmap(b []byte) error {
+	return syscall.Munmap(b)
+}
+
+// adviseRandom turns off readahead for b; slab neighbours are unrelated blocks.
+func adviseRandom(b []byte) {
+	syscall.Madvise(b, syscall.MADV_RANDOM)
+}
+
+// adviseWillNeed starts async readahead of b, which must be page aligned.
+func adviseWillNeed(b []byte) {
+	syscall.Madvise(b, syscall.MADV_WILLNEED)
+}
+
+func fdatasync(f *os.File) error {
+	return syscall.Fdatasync(int(f.Fd()))
+}
+
+// syncFileRangeWrite is SYNC_FILE_RANGE_WRITE: start writeback, don't wait.
+const syncFileRangeWrite = 0x2
+
+// startWriteback begins async writeback of [off, off+n) so a later
+// fdatasync has little left to wait for. Failure only loses the head start.
+func startWriteback(f *os.File, off, n int64) {
+	syscall.SyncFileRange(int(f.Fd()), off, n, syncFileRangeWrite)
+}
+
+// diskSpace returns an id for the filesystem holding path, the bytes
+// available on it to unprivileged users and its total size. Filesystems
+// that report no fsid are told apart by device number instead.
+func diskSpace(path string) (fs string, avail, total int64, err error) {
+	var st syscall.Statfs_t
+	if err := syscall.Statfs(path, &st); err != nil {
+		return "", 0, 0, err
+	}
+	fs = fmt.Sprintf("fsid:%x", st.Fsid.X__val)
+	if st.Fsid.X__val == [2]int32{} {
+		var sb syscall.Stat_t
+		if err := syscall.Stat(path, &sb); err != nil {
+			return "", 0, 0, err
+		}
+		fs = fmt.Sprintf("dev:%x", sb.Dev)
+	}
+	return fs, int64(st.Bavail) * int64(st.Bsize), int64(st.Blocks) * int64(st.Bsize), nil
+}
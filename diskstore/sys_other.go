@@ -0,0 +1,44 @@
+//go:build !linux
+
+package diskstore
+
+import (
+	"errors"
+	"fmt"
+	"os"
+)
+
+// openDirect falls back to buffered I/O where O_DIRECT is unavailable.
+func openDirect(path string, flag int, perm os.FileMode) (*os.File, error) {
+	return os.OpenFile(path, flag, perm)
+}
+
+// preallocate extends f to size bytes; the file may be sparse.
+func preallocate(f *os.File, size int64) error {
+	return f.Truncate(size)
+}
+
+var errNoMmap = errors.New("diskstore: mmap not supported on this platform")
+
+// mmapFile is unsupported here; callers fall back to positioned reads.
+func mmapFile(f *os.File, size int) ([]byte, error) {
+	return nil, errNoMmap
+}
+
+func munmap(b []byte) error { return nil }
+
+func adviseRandom(b []byte) {}
+
+func adviseWillNeed(b []byte) {}
+
+func fdatasync(f *os.File) error {
+	return f.Sync()
+}
+
+// startWriteback is a no-op without sync_file_range; the flush does it all.
+func startWriteback(f *os.File, off, n int64) {}
+
+// diskSpace is unsupported here, so AutoBudget is too.
+func diskSpace(path string) (fs string, avail, total int64, err error) {
+	return "", 0, 0, fmt.Errorf("diskstore: free space probing: %w", errors.ErrUnsupported)
+}
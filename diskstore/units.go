@@ -0,0 +1,57 @@
+package diskstore
+
+import (
+	"fmt"
+	"math"
+	"strconv"
+	"strings"
+)
+
+// Binary units come before their decimal counterparts and "B" last, so the
+// longest matching suffix wins.
+var sizeUnits = []struct {
+	suffix string
+	mult   float64
+}{
+	{"KiB", 1 << 10}, {"MiB", 1 << 20}, {"GiB", 1 << 30}, {"TiB", 1 << 40},
+	{"KB", 1e3}, {"MB", 1e6}, {"GB", 1e9}, {"TB", 1e12},
+	{"B", 1},
+}
+
+// ParseSize parses a byte count such as "8GiB", "500MB", "1.5 TiB" or a
+// plain integer number of bytes. Negative and out-of-range sizes are errors.
+func ParseSize(s string) (int64, error) {
+	s = strings.TrimSpace(s)
+	for _, u := range sizeUnits {
+		num, ok := strings.CutSuffix(s, u.suffix)
+		if !ok {
+			continue
+		}
+		f, err := strconv.ParseFloat(strings.TrimSpace(num), 64)
+		n := f * u.mult
+		// !(n >= 0) also catches NaN; 1<<63 rules out Inf and overflow.
+		if err != nil || !(n >= 0) || n >= 1<<63 {
+			return 0, fmt.Errorf("diskstore: invalid size %q", s)
+		}
+		return int64(n), nil
+	}
+	n, err := strconv.ParseInt(s, 10, 64)
+	if err != nil || n < 0 {
+		return 0, fmt.Errorf("diskstore: invalid size %q", s)
+	}
+	return n, nil
+}
+
+// FormatSize renders n bytes with a binary unit, e.g. "1.5 GiB".
+func FormatSize(n int64) string {
+	if n > -1024 && n < 1024 {
+		return fmt.Sprintf("%d B", n)
+	}
+	units := []string{"KiB", "MiB", "GiB", "TiB", "PiB"}
+	f, i := float64(n)/1024, 0
+	for math.Abs(f) >= 1024 && i < len(units)-1 {
+		f /= 1024
+		i++
+	}
+	return fmt.Sprintf("%.1f %s", f, units[i])
+}
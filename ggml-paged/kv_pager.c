@@ -7,13 +7,18 @@
  *
  * Each layer maintains a contiguous pinned buffer that grows as
  * positions are appended.  When the host budget is exceeded, the
- * oldest positions are compressed (zstd) and spilled to disk.
+ * buffer becomes a window over the newest positions and the oldest
+ * ones are spilled to disk, to be loaded back by kv_pager_get_range.
+ * With lock_host_memory / use_hugepages set, the buffers are instead
+ * fixed slices of one preallocated (mlock'd / hugepage) arena.
  *
  * Thread safety: a single mutex serializes all operations.
  *
  * SPDX-License-Identifier: MIT
  */
 
+#define _DEFAULT_SOURCE   /* pread, pwrite, mkstemp, MAP_ANONYMOUS, MAP_HUGETLB */
+
 #include "kv_pager.h"
 
 #include <stdio.h>
@@ -21,11 +26,17 @@
 #include <string.h>
 #include <errno.h>
 #include <pthread.h>
+#include <unistd.h>
+#include <sys/mman.h>
 #include <sys/stat.h>
 
 /* ───────────────── internal types ───────────────── */
 
 #define KVP_MAX_LAYERS 128
+#define KVP_HUGEPAGE_SIZE ((size_t)2 << 20)
+
+/* Tier 2 is tried in this order when a position needs a disk slot. */
+enum { KVP_DISK_LOCAL = 0, KVP_DISK_REMOTE = 1, KVP_DISK_TIERS = 2 };
 
 typedef enum {
     KVP_LOC_HOST = 0,   /* in pinned host RAM */
@@ -35,31 +46,53 @@ typedef enum {
 
 typedef struct {
     kvp_location_t loc;
-    int64_t        disk_offset;   /* if on disk, offset in the layer file */
+    int64_t        disk_offset;   /* if on disk, offset in the layer file (-1 = none) */
+    int            disk_tier;     /* KVP_DISK_*, valid when disk_offset >= 0 */
 } kvp_pos_meta_t;
 
+static const kvp_pos_meta_t kvp_empty_meta = { KVP_LOC_NONE, -1, 0 };
+
 typedef struct {
-    /* Pinned host buffer: [capacity, num_kv_heads, head_dim] */
+    /* Pinned host buffer: [capacity, num_kv_heads, head_dim].  Row r holds
+       position base + r; host-resident positions always lie in that window. */
     void *k_pinned;
     void *v_pinned;
     int   capacity;       /* allocated positions */
     int   count;          /* positions stored */
+    int   base;           /* position held in row 0 */
     size_t row_bytes;     /* num_kv_heads * head_dim * elem_bytes */
 
     /* Per-position metadata */
     kvp_pos_meta_t *meta;
     int              meta_cap;
+
+    int   in_arena;       /* buffers are a fixed slice of the pager arena */
 } kvp_layer_t;
 
+/* One unlinked tier 2 file, shared by all layers.  Slots of 2 * row_bytes
+   (K then V) are handed out from the free list before the file grows, so
+   it never holds more than the tier's budget. */
+typedef struct {
+    int      fd;          /* -1 until the first spill */
+    int64_t  used;        /* bytes in live slots */
+    int64_t  end;         /* file size: bytes of slots handed out */
+    int64_t *free;        /* offsets of released slots below end */
+    int      nfree;
+    int      free_cap;
+} kvp_disk_t;
+
 struct kv_pager {
     kv_pager_config_t config;
     kvp_layer_t       layers[KVP_MAX_LAYERS];
     int64_t           host_used;
+    kvp_disk_t        disk[KVP_DISK_TIERS];
     pthread_mutex_t   mutex;
 
-    /* Staging buffer for disk→host loads */
-    void   *stage_buf;
-    size_t  stage_cap;
+    /* Preallocated tier 1 arena (lock_host_memory / use_hugepages) */
+    void   *arena;
+    size_t  arena_bytes;
+    int     arena_locked;
+    int     arena_hugepages;
 };
 
 /* ───────────────── helpers ───────────────── */
@@ -75,20 +108,26 @@ static size_t row_bytes(const kv_pager_config_t *cfg) {
     return (size_t)cfg->num_kv_heads * cfg->head_dim * cfg->elem_bytes;
 }
 
-/* Allocate or grow pinned host buffer for a layer. */
+/* Allocate or grow pinned host buffer for a layer.  With a budget, growth
+   is also held to the layer's even share of it (share), so the first layer
+   to fill cannot starve the rest once positions start spilling. */
 static int kvp_ensure_capacity(kvp_layer_t *layer, int need,
-                               size_t rb, int64_t *host_used, int64_t budget)
+                               size_t rb, int64_t *host_used, int64_t budget,
+                               int64_t share)
 {
     if (need <= layer->capacity) return 0;
+    if (layer->in_arena) return -1;   /* arena slices never grow */
 
     int new_cap = layer->capacity ? layer->capacity : 256;
     while (new_cap < need) new_cap *= 2;
 
     /* Check budget */
     int64_t added = (int64_t)(new_cap - layer->capacity) * rb * 2; /* K + V */
-    if (*host_used + added > budget && budget > 0) {
+    int64_t avail = budget - *host_used;
+    int64_t left  = share - (int64_t)layer->capacity * rb * 2;
+    if (share > 0 && left < avail) avail = left;
+    if (added > avail && budget > 0) {
         /* Clamp to budget */
-        int64_t avail = budget - *host_used;
         if (avail <= 0) return -1;
         new_cap = layer->capacity + (int)(avail / (rb * 2));
         if (new_cap <= layer->capacity) return -1;
@@ -101,23 +140,337 @@ static int kvp_ensure_capacity(kvp_layer_t *layer, int need,
     layer->k_pinned = new_k;
     layer->v_pinned = new_v;
 
-    /* Grow metadata */
-    if (new_cap > layer->meta_cap) {
-        kvp_pos_meta_t *new_meta = (kvp_pos_meta_t *)realloc(
-            layer->meta, (size_t)new_cap * sizeof(kvp_pos_meta_t));
-        if (!new_meta) return -1;
-        for (int i = layer->meta_cap; i < new_cap; i++)
-            new_meta[i].loc = KVP_LOC_NONE;
-        layer->meta = new_meta;
-        layer->meta_cap = new_cap;
-    }
-
     *host_used += added;
     layer->capacity = new_cap;
     layer->row_bytes = rb;
+    return new_cap >= need ? 0 : -1;   /* clamped short of need */
+}
+
+/* Grow per-position metadata to cover positions [0, need).  Positions are
+   absolute, so this is independent of the host window. */
+static int kvp_ensure_meta(kvp_layer_t *layer, int need) {
+    if (need <= layer->meta_cap) return 0;
+
+    int new_cap = layer->meta_cap ? layer->meta_cap : 256;
+    while (new_cap < need) new_cap *= 2;
+
+    kvp_pos_meta_t *new_meta = (kvp_pos_meta_t *)realloc(
+        layer->meta, (size_t)new_cap * sizeof(kvp_pos_meta_t));
+    if (!new_meta) return -1;
+    for (int i = layer->meta_cap; i < new_cap; i++)
+        new_meta[i] = kvp_empty_meta;
+    layer->meta = new_meta;
+    layer->meta_cap = new_cap;
+    return 0;
+}
+
+/* ───────────────── tier 2 ───────────────── */
+
+static const char *kvp_disk_path(const kv_pager_t *p, int tier) {
+    return tier == KVP_DISK_LOCAL ? p->config.local_disk_path
+                                  : p->config.remote_disk_path;
+}
+
+static int64_t kvp_disk_budget(const kv_pager_t *p, int tier) {
+    return tier == KVP_DISK_LOCAL ? p->config.local_disk_budget
+                                  : p->config.remote_disk_budget;
+}
+
+/* Return a disk tier's file, creating it on first use.  The file is
+   unlinked right away so nothing outlives the pager, even on a crash. */
+static int kvp_disk_fd(kv_pager_t *p, int tier) {
+    kvp_disk_t *d = &p->disk[tier];
+    if (d->fd >= 0) return d->fd;
+
+    char path[4096];
+    int n = snprintf(path, sizeof(path), "%s/kv_pager-XXXXXX",
+                     kvp_disk_path(p, tier));
+    if (n < 0 || (size_t)n >= sizeof(path)) return -1;
+    int fd = mkstemp(path);
+    if (fd < 0) {
+        fprintf(stderr, "kv_pager: create %s: %s\n", path, strerror(errno));
+        return -1;
+    }
+    unlink(path);
+    d->fd = fd;
+    return fd;
+}
+
+/* Forget every slot of a disk tier and give its file's space back. */
+static void kvp_disk_reset(kv_pager_t *p, int tier) {
+    kvp_disk_t *d = &p->disk[tier];
+    d->used = d->end = 0;
+    d->nfree = 0;
+    if (d->fd >= 0 && ftruncate(d->fd, 0) != 0)
+        fprintf(stderr, "kv_pager: truncate %s file: %s\n",
+                kvp_disk_path(p, tier), strerror(errno));
+}
+
+/* Give position pos of layer l a disk slot on the first tier with room,
+   reusing a released slot before growing the file.  A disk budget of 0
+   means unlimited, as for host_budget_bytes. */
+static int kvp_disk_alloc(kv_pager_t *p, int l, int pos) {
+    kvp_pos_meta_t *m = &p->layers[l].meta[pos];
+    if (m->disk_offset >= 0) return 0;
+
+    int64_t slot = 2 * (int64_t)row_bytes(&p->config);   /* K + V */
+    for (int t = 0; t < KVP_DISK_TIERS; t++) {
+        kvp_disk_t *d = &p->disk[t];
+        int64_t budget = kvp_disk_budget(p, t);
+        if (!kvp_disk_path(p, t)) continue;
+        if (budget > 0 && d->used + slot > budget) continue;
+        if (kvp_disk_fd(p, t) < 0) continue;
+
+        int64_t off;
+        if (d->nfree > 0) {
+            off = d->free[--d->nfree];
+        } else {
+            /* Size the free list for every slot up front, so releasing
+               one never has to allocate. */
+            int slots = (int)(d->end / slot) + 1;
+            if (slots > d->free_cap) {
+                int new_cap = d->free_cap ? d->free_cap : 256;
+                while (new_cap < slots) new_cap *= 2;
+                int64_t *nf = (int64_t *)realloc(
+                    d->free, (size_t)new_cap * sizeof(int64_t));
+                if (!nf) continue;
+                d->free = nf;
+                d->free_cap = new_cap;
+            }
+            off = d->end;
+            d->end += slot;
+        }
+        m->disk_tier   = t;
+        m->disk_offset = off;
+        d->used += slot;
+        return 0;
+    }
+    return -1;
+}
+
+static void kvp_disk_free(kv_pager_t *p, kvp_pos_meta_t *m) {
+    if (m->disk_offset < 0) return;
+    kvp_disk_t *d = &p->disk[m->disk_tier];
+    d->used -= 2 * (int64_t)row_bytes(&p->config);
+    d->free[d->nfree++] = m->disk_offset;
+    m->disk_offset = -1;
+    if (d->used == 0) kvp_disk_reset(p, m->disk_tier);
+}
+
+static int kvp_pwrite_all(int fd, const void *buf, size_t n, int64_t off) {
+    const char *b = (const char *)buf;
+    while (n > 0) {
+        ssize_t w = pwrite(fd, b, n, (off_t)off);
+        if (w < 0 && errno == EINTR) continue;
+        if (w <= 0) return -1;
+        b += w; n -= (size_t)w; off += w;
+    }
+    return 0;
+}
+
+static int kvp_pread_all(int fd, void *buf, size_t n, int64_t off) {
+    char *b = (char *)buf;
+    while (n > 0) {
+        ssize_t r = pread(fd, b, n, (off_t)off);
+        if (r < 0 && errno == EINTR) continue;
+        if (r <= 0) return -1;   /* a short file is an error too */
+        b += r; n -= (size_t)r; off += r;
+    }
+    return 0;
+}
+
+/* Write K and V rows for position pos to its disk slot and mark it on disk. */
+static int kvp_write_disk(kv_pager_t *p, int l, int pos,
+                          const void *k_row, const void *v_row) {
+    kvp_layer_t *lyr = &p->layers[l];
+    kvp_pos_meta_t *m = &lyr->meta[pos];
+    size_t rb = row_bytes(&p->config);
+
+    if (kvp_disk_alloc(p, l, pos) != 0) return -1;
+    int fd = p->disk[m->disk_tier].fd;
+    if (kvp_pwrite_all(fd, k_row, rb, m->disk_offset) != 0 ||
+        kvp_pwrite_all(fd, v_row, rb, m->disk_offset + (int64_t)rb) != 0) {
+        fprintf(stderr, "kv_pager: spill layer %d pos %d: %s\n",
+                l, pos, strerror(errno));
+        return -1;
+    }
+    m->loc = KVP_LOC_DISK;
+    return 0;
+}
+
+/* Spill host-resident position pos.  The host row is left as is; the
+   caller reuses it. */
+static int kvp_spill(kv_pager_t *p, int l, int pos) {
+    kvp_layer_t *lyr = &p->layers[l];
+    size_t row = (size_t)(pos - lyr->base) * row_bytes(&p->config);
+    return kvp_write_disk(p, l, pos, (char *)lyr->k_pinned + row,
+                          (char *)lyr->v_pinned + row);
+}
+
+/* Load on-disk position pos into its host row, which must be in the window,
+   and release its disk slot. */
+static int kvp_load(kv_pager_t *p, int l, int pos) {
+    kvp_layer_t *lyr = &p->layers[l];
+    kvp_pos_meta_t *m = &lyr->meta[pos];
+    size_t rb = row_bytes(&p->config);
+    size_t row = (size_t)(pos - lyr->base) * rb;
+
+    int fd = p->disk[m->disk_tier].fd;
+    if (kvp_pread_all(fd, (char *)lyr->k_pinned + row, rb, m->disk_offset) != 0 ||
+        kvp_pread_all(fd, (char *)lyr->v_pinned + row, rb,
+                      m->disk_offset + (int64_t)rb) != 0) {
+        fprintf(stderr, "kv_pager: load layer %d pos %d: %s\n",
+                l, pos, strerror(errno));
+        return -1;
+    }
+    kvp_disk_free(p, m);
+    m->loc = KVP_LOC_HOST;
+    return 0;
+}
+
+/* Move layer l's host window to start at position nb.  Host rows that fall
+   outside the new window are spilled first; on-disk positions that fall
+   inside it stay on disk until kvp_fill loads them.  On failure some rows
+   may already be on disk, which is still a consistent state. */
+static int kvp_slide(kv_pager_t *p, int l, int nb) {
+    kvp_layer_t *lyr = &p->layers[l];
+    size_t rb = row_bytes(&p->config);
+    int ob = lyr->base, cap = lyr->capacity;
+    if (nb == ob) return 0;
+
+    int end = ob + cap < lyr->count ? ob + cap : lyr->count;
+    for (int pos = ob; pos < end; pos++) {
+        if (lyr->meta[pos].loc == KVP_LOC_HOST &&
+            (pos < nb || pos >= nb + cap) &&
+            kvp_spill(p, l, pos) != 0)
+            return -1;
+    }
+
+    /* Rows in both windows shift by ob - nb. */
+    int lo = ob > nb ? ob : nb;
+    int hi = (ob < nb ? ob : nb) + cap;
+    if (lo < hi) {
+        memmove((char *)lyr->k_pinned + (size_t)(lo - nb) * rb,
+                (char *)lyr->k_pinned + (size_t)(lo - ob) * rb,
+                (size_t)(hi - lo) * rb);
+        memmove((char *)lyr->v_pinned + (size_t)(lo - nb) * rb,
+                (char *)lyr->v_pinned + (size_t)(lo - ob) * rb,
+                (size_t)(hi - lo) * rb);
+    }
+    lyr->base = nb;
+    return 0;
+}
+
+/* Make position pos, at or past the window start, addressable in layer l's
+   host buffer: grow the buffer while the budget allows, else slide the
+   window forward, spilling its oldest rows to disk. */
+static int kvp_make_room(kv_pager_t *p, int l, int pos) {
+    kvp_layer_t *lyr = &p->layers[l];
+    int64_t budget = p->config.host_budget_bytes;
+    int64_t share  = p->config.num_layers > 0 ? budget / p->config.num_layers : 0;
+    if (kvp_ensure_capacity(lyr, pos - lyr->base + 1, row_bytes(&p->config),
+                            &p->host_used, budget, share) == 0)
+        return 0;
+    if (lyr->capacity == 0) return -1;
+
+    /* Spill a quarter of the window at a time so appends amortize the I/O. */
+    int nb = lyr->base + (lyr->capacity + 3) / 4;
+    if (nb < pos - lyr->capacity + 1) nb = pos - lyr->capacity + 1;
+    return kvp_slide(p, l, nb);
+}
+
+/* Load every on-disk position in [start, start+count), which must lie in
+   the host window. */
+static int kvp_fill(kv_pager_t *p, int l, int start, int count) {
+    kvp_layer_t *lyr = &p->layers[l];
+    for (int pos = start; pos < start + count; pos++) {
+        if (lyr->meta[pos].loc == KVP_LOC_DISK && kvp_load(p, l, pos) != 0)
+            return -1;
+    }
     return 0;
 }
 
+/* Map the tier 1 arena and hand each configured layer a fixed slice.
+   Leaves the pager on the realloc path if anything fails. */
+static void kvp_arena_init(kv_pager_t *p) {
+    const kv_pager_config_t *cfg = &p->config;
+    size_t rb = row_bytes(cfg);
+    if (cfg->num_layers <= 0 || cfg->num_layers > KVP_MAX_LAYERS || rb == 0)
+        return;
+
+    size_t bytes = (size_t)cfg->host_budget_bytes;
+    void *mem = MAP_FAILED;
+
+#ifdef MAP_HUGETLB
+    if (cfg->use_hugepages) {
+        size_t huge = (bytes + KVP_HUGEPAGE_SIZE - 1) & ~(KVP_HUGEPAGE_SIZE - 1);
+        mem = mmap(NULL, huge, PROT_READ | PROT_WRITE,
+                   MAP_PRIVATE | MAP_ANONYMOUS | MAP_HUGETLB, -1, 0);
+        if (mem != MAP_FAILED) {
+            bytes = huge;
+            p->arena_hugepages = 1;
+        }
+    }
+#endif
+    if (mem == MAP_FAILED) {
+        mem = mmap(NULL, bytes, PROT_READ | PROT_WRITE,
+                   MAP_PRIVATE | MAP_ANONYMOUS, -1, 0);
+        if (mem == MAP_FAILED) {
+            fprintf(stderr, "kv_pager: arena mmap of %zu bytes failed: %s\n",
+                    bytes, strerror(errno));
+            return;
+        }
+#ifdef MADV_HUGEPAGE
+        if (cfg->use_hugepages) madvise(mem, bytes, MADV_HUGEPAGE);
+#endif
+    }
+
+    if (cfg->lock_host_memory) {
+        if (mlock(mem, bytes) == 0)
+            p->arena_locked = 1;
+        else
+            fprintf(stderr, "kv_pager: mlock of %zu bytes failed: %s "
+                            "(arena may be swapped)\n", bytes, strerror(errno));
+    }
+
+    /* K and V halves of each layer slice hold the same number of rows. */
+    size_t slice = bytes / (size_t)cfg->num_layers;
+    int cap = (int)(slice / (rb * 2));
+    if (cap <= 0) {
+        munmap(mem, bytes);
+        p->arena_locked = p->arena_hugepages = 0;
+        return;
+    }
+
+    for (int l = 0; l < cfg->num_layers; l++) {
+        kvp_layer_t *lyr = &p->layers[l];
+        kvp_pos_meta_t *meta = (kvp_pos_meta_t *)malloc(
+            (size_t)cap * sizeof(kvp_pos_meta_t));
+        if (!meta) {
+            for (int j = 0; j < l; j++) {
+                free(p->layers[j].meta);
+                memset(&p->layers[j], 0, sizeof(kvp_layer_t));
+            }
+            munmap(mem, bytes);
+            p->arena_locked = p->arena_hugepages = 0;
+            return;
+        }
+        for (int i = 0; i < cap; i++) meta[i] = kvp_empty_meta;
+
+        lyr->k_pinned  = (char *)mem + (size_t)l * slice;
+        lyr->v_pinned  = (char *)lyr->k_pinned + (size_t)cap * rb;
+        lyr->capacity  = cap;
+        lyr->row_bytes = rb;
+        lyr->meta      = meta;
+        lyr->meta_cap  = cap;
+        lyr->in_arena  = 1;
+    }
+
+    p->arena       = mem;
+    p->arena_bytes = bytes;
+    p->host_used   = (int64_t)cfg->num_layers * cap * (int64_t)rb * 2;
+}
+
 /* ───────────────── public API ───────────────── */
 
 kv_pager_t *kv_pager_create(const kv_pager_config_t *config) {
@@ -132,6 +485,13 @@ kv_pager_t *kv_pager_create(const kv_pager_config_t *config) {
     if (config->local_disk_path)  ensure_dir(config->local_disk_path);
     if (config->remote_disk_path) ensure_dir(config->remote_disk_path);
 
+    if ((config->lock_host_memory || config->use_hugepages) &&
+        config->host_budget_bytes > 0)
+        kvp_arena_init(p);
+
+    for (int t = 0; t < KVP_DISK_TIERS; t++)
+        p->disk[t].fd = -1;
+
     return p;
 }
 
@@ -139,11 +499,20 @@ void kv_pager_destroy(kv_pager_t *pager) {
     if (!pager) return;
     for (int l = 0; l < KVP_MAX_LAYERS; l++) {
         kvp_layer_t *layer = &pager->layers[l];
-        free(layer->k_pinned);
-        free(layer->v_pinned);
+        if (!layer->in_arena) {
+            free(layer->k_pinned);
+            free(layer->v_pinned);
+        }
         free(layer->meta);
     }
-    free(pager->stage_buf);
+    for (int t = 0; t < KVP_DISK_TIERS; t++) {
+        if (pager->disk[t].fd >= 0) close(pager->disk[t].fd);
+        free(pager->disk[t].free);
+    }
+    if (pager->arena) {
+        if (pager->arena_locked) munlock(pager->arena, pager->arena_bytes);
+        munmap(pager->arena, pager->arena_bytes);
+    }
     pthread_mutex_destroy(&pager->mutex);
     free(pager);
 }
@@ -159,15 +528,15 @@ int kv_pager_append(kv_pager_t *pager, int layer,
     size_t rb = row_bytes(&pager->config);
     int pos = lyr->count;
 
-    if (kvp_ensure_capacity(lyr, pos + 1, rb,
-                            &pager->host_used,
-                            pager->config.host_budget_bytes) != 0) {
+    if (kvp_ensure_meta(lyr, pos + 1) != 0 ||
+        kvp_make_room(pager, layer, pos) != 0) {
         pthread_mutex_unlock(&pager->mutex);
         return -1;
     }
 
-    memcpy((char *)lyr->k_pinned + (size_t)pos * rb, k_data, rb);
-    memcpy((char *)lyr->v_pinned + (size_t)pos * rb, v_data, rb);
+    size_t row = (size_t)(pos - lyr->base) * rb;
+    memcpy((char *)lyr->k_pinned + row, k_data, rb);
+    memcpy((char *)lyr->v_pinned + row, v_data, rb);
     lyr->meta[pos].loc = KVP_LOC_HOST;
     lyr->count = pos + 1;
 
@@ -185,15 +554,27 @@ int kv_pager_store(kv_pager_t *pager, int layer, int pos,
     kvp_layer_t *lyr = &pager->layers[layer];
     size_t rb = row_bytes(&pager->config);
 
-    if (kvp_ensure_capacity(lyr, pos + 1, rb,
-                            &pager->host_used,
-                            pager->config.host_budget_bytes) != 0) {
+    if (kvp_ensure_meta(lyr, pos + 1) != 0) {
         pthread_mutex_unlock(&pager->mutex);
         return -1;
     }
 
-    memcpy((char *)lyr->k_pinned + (size_t)pos * rb, k_data, rb);
-    memcpy((char *)lyr->v_pinned + (size_t)pos * rb, v_data, rb);
+    /* Positions behind the window are written straight to their disk slot. */
+    if (pos < lyr->base) {
+        int rc = kvp_write_disk(pager, layer, pos, k_data, v_data);
+        pthread_mutex_unlock(&pager->mutex);
+        return rc;
+    }
+
+    if (kvp_make_room(pager, layer, pos) != 0) {
+        pthread_mutex_unlock(&pager->mutex);
+        return -1;
+    }
+
+    size_t row = (size_t)(pos - lyr->base) * rb;
+    memcpy((char *)lyr->k_pinned + row, k_data, rb);
+    memcpy((char *)lyr->v_pinned + row, v_data, rb);
+    kvp_disk_free(pager, &lyr->meta[pos]);   /* any disk copy is now stale */
     lyr->meta[pos].loc = KVP_LOC_HOST;
     if (pos >= lyr->count) lyr->count = pos + 1;
 
@@ -204,7 +585,7 @@ int kv_pager_store(kv_pager_t *pager, int layer, int pos,
 int kv_pager_get_range(kv_pager_t *pager, int layer, int start, int count,
                        void **k_out, void **v_out)
 {
-    if (!pager || layer < 0 || layer >= KVP_MAX_LAYERS) return -1;
+    if (!pager || layer < 0 || layer >= KVP_MAX_LAYERS || start < 0) return -1;
 
     pthread_mutex_lock(&pager->mutex);
 
@@ -222,10 +603,21 @@ int kv_pager_get_range(kv_pager_t *pager, int layer, int start, int count,
         return 0;
     }
 
-    /* TODO: load any disk-resident positions back into host buffer */
+    /* Once positions have spilled, at most one window's worth is returned;
+       the window moves to start and disk-resident positions are loaded. */
+    if (count > lyr->capacity) count = lyr->capacity;
+    if ((start < lyr->base || start + count > lyr->base + lyr->capacity) &&
+        kvp_slide(pager, layer, start) != 0) {
+        pthread_mutex_unlock(&pager->mutex);
+        return -1;
+    }
+    if (kvp_fill(pager, layer, start, count) != 0) {
+        pthread_mutex_unlock(&pager->mutex);
+        return -1;
+    }
 
-    if (k_out) *k_out = (char *)lyr->k_pinned + (size_t)start * rb;
-    if (v_out) *v_out = (char *)lyr->v_pinned + (size_t)start * rb;
+    if (k_out) *k_out = (char *)lyr->k_pinned + (size_t)(start - lyr->base) * rb;
+    if (v_out) *v_out = (char *)lyr->v_pinned + (size_t)(start - lyr->base) * rb;
 
     pthread_mutex_unlock(&pager->mutex);
     return count;
@@ -253,8 +645,10 @@ int kv_pager_remove_range(kv_pager_t *pager, int start, int count) {
 
         /* Mark positions as empty */
         for (int p = start; p < end; p++) {
-            if (p < lyr->meta_cap)
+            if (p < lyr->meta_cap) {
+                kvp_disk_free(pager, &lyr->meta[p]);
                 lyr->meta[p].loc = KVP_LOC_NONE;
+            }
         }
 
         /* If removing from the tail, shrink count */
@@ -268,6 +662,8 @@ int kv_pager_remove_range(kv_pager_t *pager, int start, int count) {
                 }
             }
             lyr->count = new_count;
+            /* Keep appends inside the window; every row past count is empty. */
+            if (lyr->base > lyr->count) lyr->base = lyr->count;
         }
     }
 
@@ -281,10 +677,14 @@ int kv_pager_clear(kv_pager_t *pager) {
     pthread_mutex_lock(&pager->mutex);
 
     for (int l = 0; l < KVP_MAX_LAYERS; l++) {
-        pager->layers[l].count = 0;
-        for (int p = 0; p < pager->layers[l].meta_cap; p++)
-            pager->layers[l].meta[p].loc = KVP_LOC_NONE;
+        kvp_layer_t *lyr = &pager->layers[l];
+        lyr->count = 0;
+        lyr->base  = 0;
+        for (int p = 0; p < lyr->meta_cap; p++)
+            lyr->meta[p] = kvp_empty_meta;
     }
+    for (int t = 0; t < KVP_DISK_TIERS; t++)
+        kvp_disk_reset(pager, t);
 
     pthread_mutex_unlock(&pager->mutex);
     return 0;
@@ -294,8 +694,12 @@ kv_pager_stats_t kv_pager_get_stats(const kv_pager_t *pager) {
     kv_pager_stats_t s = {0};
     if (!pager) return s;
 
-    s.host_capacity_bytes = pager->config.host_budget_bytes;
-    s.host_used_bytes     = pager->host_used;
+    s.host_capacity_bytes    = pager->config.host_budget_bytes;
+    s.host_used_bytes        = pager->host_used;
+    s.disk_local_used_bytes  = pager->disk[KVP_DISK_LOCAL].used;
+    s.disk_remote_used_bytes = pager->disk[KVP_DISK_REMOTE].used;
+    s.host_locked            = pager->arena_locked;
+    s.host_hugepages         = pager->arena_hugepages;
 
     for (int l = 0; l < KVP_MAX_LAYERS; l++) {
         const kvp_layer_t *lyr = &pager->layers[l];
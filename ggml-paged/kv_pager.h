@@ -40,11 +40,18 @@ typedef struct {
     /* Tier 1: pinned host memory budget (bytes). */
     int64_t host_budget_bytes;
 
+    /* Tier 1 backing.  When either flag is set (and host_budget_bytes > 0)
+       the whole budget is preallocated as one arena, split evenly across
+       num_layers.  Both fall back silently if the OS refuses; check
+       kv_pager_stats_t.host_locked / host_hugepages for the outcome. */
+    int     lock_host_memory;      /* mlock the arena so it is never swapped */
+    int     use_hugepages;         /* MAP_HUGETLB, else transparent hugepages */
+
     /* Tier 2: disk paths (NULL to disable). */
     const char *local_disk_path;   /* fast SSD tier */
     const char *remote_disk_path;  /* slow NFS/HDD tier */
-    int64_t local_disk_budget;     /* bytes */
-    int64_t remote_disk_budget;    /* bytes */
+    int64_t local_disk_budget;     /* bytes (0 = unlimited) */
+    int64_t remote_disk_budget;    /* bytes (0 = unlimited); used once local is full */
 } kv_pager_config_t;
 
 typedef struct {
@@ -55,6 +62,8 @@ typedef struct {
     int     total_positions;       /* across all layers */
     int     host_positions;        /* positions in host RAM */
     int     disk_positions;        /* positions on disk */
+    int     host_locked;           /* tier 1 arena is mlock'd */
+    int     host_hugepages;        /* tier 1 arena uses explicit hugepages */
 } kv_pager_stats_t;
 
 /* ───────────────── lifetime ───────────────── */
@@ -94,8 +103,10 @@ int kv_pager_store(kv_pager_t *pager, int layer, int pos,
  *   V: [count, num_kv_heads, head_dim]
  *
  * If some positions are on disk, they are loaded into the host buffer first.
- * The pointers remain valid until the next call to kv_pager_get_range()
- * or kv_pager_destroy().
+ * Once a layer has spilled, at most its host capacity is returned per call,
+ * and host rows outside the requested range may be spilled to make room.
+ * The pointers remain valid until the next call to kv_pager_get_range(),
+ * kv_pager_append(), kv_pager_store() or kv_pager_destroy().
  *
  * @param pager   The pager instance.
  * @param layer   Layer index.
@@ -119,7 +130,7 @@ int kv_pager_get_layer(kv_pager_t *pager, int layer,
 
 /**
  * Remove all positions in range [start, start+count) for all layers.
- * Frees host memory and deletes disk files.
+ * Frees host memory and releases the disk slots they used.
  */
 int kv_pager_remove_range(kv_pager_t *pager, int start, int count);
 
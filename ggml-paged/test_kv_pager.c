@@ -0,0 +1,273 @@
+/**
+ * test_kv_pager.c — Tests for the host-side KV page manager.
+ *
+ * Appends more positions than tier 1 holds, so rows spill to the local
+ * and then the remote disk tier, and checks that every row comes back
+ * intact through kv_pager_get_range().  Each test runs with realloc'd
+ * host buffers and again with the mlock'd arena.  Build with
+ * -fsanitize=address,undefined to catch bad window arithmetic.
+ *
+ * SPDX-License-Identifier: MIT
+ */
+
+#define _DEFAULT_SOURCE   /* mkdtemp */
+
+#include "kv_pager.h"
+
+#include <dirent.h>
+#include <stdio.h>
+#include <stdlib.h>
+#include <string.h>
+#include <unistd.h>
+#include <sys/stat.h>
+
+#define NUM_LAYERS 3
+#define NUM_HEADS  2
+#define HEAD_DIM   4
+#define ROW_FLOATS (NUM_HEADS * HEAD_DIM)
+#define ROW_BYTES  (ROW_FLOATS * (int64_t)sizeof(float))
+#define WINDOW     100                /* host rows per layer */
+#define NUM_POS    1500
+
+static char local_dir[64], remote_dir[64];
+static int  use_arena;
+
+/* Row contents are a function of (layer, pos, gen) so any misplaced or
+   stale row shows up as a mismatch. */
+static void make_row(float *k, float *v, int layer, int pos, int gen) {
+    for (int i = 0; i < ROW_FLOATS; i++) {
+        k[i] = (float)(layer * 100000 + pos) + (float)i * 0.001f + (float)gen * 0.5f;
+        v[i] = -k[i];
+    }
+}
+
+static kv_pager_t *make_pager(int64_t local_budget) {
+    kv_pager_config_t cfg = {0};
+    cfg.num_layers         = NUM_LAYERS;
+    cfg.num_kv_heads       = NUM_HEADS;
+    cfg.head_dim           = HEAD_DIM;
+    cfg.elem_bytes         = sizeof(float);
+    cfg.host_budget_bytes  = (int64_t)NUM_LAYERS * WINDOW * ROW_BYTES * 2;
+    cfg.lock_host_memory   = use_arena;
+    cfg.local_disk_path    = local_dir;
+    cfg.remote_disk_path   = remote_dir;
+    cfg.local_disk_budget  = local_budget;
+    cfg.remote_disk_budget = 0;
+    return kv_pager_create(&cfg);
+}
+
+static int append_rows(kv_pager_t *p, int from, int to) {
+    float k[ROW_FLOATS], v[ROW_FLOATS];
+    for (int pos = from; pos < to; pos++) {
+        for (int l = 0; l < NUM_LAYERS; l++) {
+            make_row(k, v, l, pos, 0);
+            if (kv_pager_append(p, l, k, v) != pos) {
+                printf("FAIL (append layer %d pos %d)\n", l, pos);
+                return 1;
+            }
+        }
+    }
+    return 0;
+}
+
+/* Read [start, start+count) of a layer back, a window at a time, and
+   compare each row against generation gen[pos]. */
+static int check_rows(kv_pager_t *p, int layer, int start, int count,
+                      const int *gen) {
+    int done = 0;
+    while (done < count) {
+        void *K, *V;
+        int got = kv_pager_get_range(p, layer, start + done, count - done, &K, &V);
+        if (got <= 0) {
+            printf("FAIL (get_range layer %d pos %d returned %d)\n",
+                   layer, start + done, got);
+            return 1;
+        }
+        for (int j = 0; j < got; j++) {
+            float k[ROW_FLOATS], v[ROW_FLOATS];
+            int pos = start + done + j;
+            make_row(k, v, layer, pos, gen[pos]);
+            if (memcmp((char *)K + j * ROW_BYTES, k, ROW_BYTES) ||
+                memcmp((char *)V + j * ROW_BYTES, v, ROW_BYTES)) {
+                printf("FAIL (layer %d pos %d mismatch)\n", layer, pos);
+                return 1;
+            }
+        }
+        done += got;
+    }
+    return 0;
+}
+
+static int check_all(kv_pager_t *p, int count, const int *gen) {
+    for (int l = 0; l < NUM_LAYERS; l++)
+        if (check_rows(p, l, 0, count, gen)) return 1;
+    return 0;
+}
+
+/* Total size of this process's open files under dir.  The pager unlinks
+   its tier 2 files, so they are only reachable through /proc/self/fd. */
+static int64_t open_bytes_under(const char *dir) {
+    int64_t total = 0;
+    DIR *fds = opendir("/proc/self/fd");
+    if (!fds) return -1;
+    struct dirent *e;
+    while ((e = readdir(fds)) != NULL) {
+        char link[300], target[4096];
+        snprintf(link, sizeof(link), "/proc/self/fd/%s", e->d_name);
+        ssize_t n = readlink(link, target, sizeof(target) - 1);
+        if (n <= 0) continue;
+        target[n] = '\0';
+        struct stat st;
+        if (strncmp(target, dir, strlen(dir)) == 0 && stat(link, &st) == 0)
+            total += st.st_size;
+    }
+    closedir(fds);
+    return total;
+}
+
+/* ───────── tests ───────── */
+
+static int test_spill_and_reload(void) {
+    printf("  test: spill %d positions past a %d-row window ... ", NUM_POS, WINDOW);
+    int64_t local_budget = 300 * ROW_BYTES * 2;
+    kv_pager_t *p = make_pager(local_budget);
+    int gen[NUM_POS] = {0};
+    int fail = append_rows(p, 0, NUM_POS);
+
+    kv_pager_stats_t s = kv_pager_get_stats(p);
+    if (!fail && (s.disk_positions == 0 || s.disk_local_used_bytes > local_budget ||
+                  s.disk_remote_used_bytes == 0)) {
+        printf("FAIL (disk positions %d, local %lld of %lld, remote %lld)\n",
+               s.disk_positions, (long long)s.disk_local_used_bytes,
+               (long long)local_budget, (long long)s.disk_remote_used_bytes);
+        fail = 1;
+    }
+    if (!fail) fail = check_all(p, NUM_POS, gen);
+    if (!fail) printf("PASS\n");
+    kv_pager_destroy(p);
+    return fail;
+}
+
+static int test_store(void) {
+    printf("  test: store behind and inside the window ... ");
+    kv_pager_t *p = make_pager(0);
+    int gen[NUM_POS] = {0};
+    int fail = append_rows(p, 0, NUM_POS);
+
+    float k[ROW_FLOATS], v[ROW_FLOATS];
+    for (int pos = 0; pos < NUM_POS && !fail; pos += 7) {
+        gen[pos] = 1;
+        for (int l = 0; l < NUM_LAYERS; l++) {
+            make_row(k, v, l, pos, 1);
+            if (kv_pager_store(p, l, pos, k, v) != 0) {
+                printf("FAIL (store layer %d pos %d)\n", l, pos);
+                fail = 1;
+                break;
+            }
+        }
+    }
+    if (!fail) fail = check_all(p, NUM_POS, gen);
+    /* Jump the window around within one layer. */
+    if (!fail) fail = check_rows(p, 1, 1200, 300, gen);
+    if (!fail) fail = check_rows(p, 1, 10, 50, gen);
+    if (!fail) printf("PASS\n");
+    kv_pager_destroy(p);
+    return fail;
+}
+
+static int test_disk_budget(void) {
+    printf("  test: tier 2 files stay within their budget ... ");
+    int64_t local_budget = 300 * ROW_BYTES * 2;
+    kv_pager_t *p = make_pager(local_budget);
+    int gen[4000] = {0};
+    int fail = 0;
+
+    /* Slots freed by reloads and removals must be reused, not left behind
+       in the file while positions further on take new ones. */
+    for (int round = 0; round < 4 && !fail; round++) {
+        int base = round * 1000;
+        fail = append_rows(p, base, base + 1000);
+        for (int l = 0; l < NUM_LAYERS && !fail; l++)
+            fail = check_rows(p, l, base, 1000, gen);
+        if (!fail) kv_pager_remove_range(p, base, 700);
+
+        int64_t held = open_bytes_under(local_dir);
+        kv_pager_stats_t s = kv_pager_get_stats(p);
+        if (!fail && (held > local_budget || s.disk_local_used_bytes > held)) {
+            printf("FAIL (round %d: local file %lld bytes, %lld used, budget %lld)\n",
+                   round, (long long)held, (long long)s.disk_local_used_bytes,
+                   (long long)local_budget);
+            fail = 1;
+        }
+    }
+    if (!fail) printf("PASS\n");
+    kv_pager_destroy(p);
+    return fail;
+}
+
+static int test_remove_and_clear(void) {
+    printf("  test: remove_range, re-append and clear ... ");
+    kv_pager_t *p = make_pager(0);
+    int gen[NUM_POS] = {0};
+    int fail = append_rows(p, 0, NUM_POS);
+
+    if (!fail) {
+        kv_pager_remove_range(p, 1000, NUM_POS - 1000);
+        kv_pager_stats_t s = kv_pager_get_stats(p);
+        if (s.total_positions != NUM_LAYERS * 1000) {
+            printf("FAIL (%d positions after remove, want %d)\n",
+                   s.total_positions, NUM_LAYERS * 1000);
+            fail = 1;
+        }
+    }
+    if (!fail) fail = append_rows(p, 1000, 1400);
+    if (!fail) fail = check_all(p, 1400, gen);
+
+    if (!fail) {
+        kv_pager_clear(p);
+        kv_pager_stats_t s = kv_pager_get_stats(p);
+        if (s.total_positions != 0 || s.disk_local_used_bytes != 0 ||
+            s.disk_remote_used_bytes != 0) {
+            printf("FAIL (clear left %d positions, %lld + %lld disk bytes)\n",
+                   s.total_positions, (long long)s.disk_local_used_bytes,
+                   (long long)s.disk_remote_used_bytes);
+            fail = 1;
+        }
+    }
+    if (!fail) fail = append_rows(p, 0, 500);
+    if (!fail) fail = check_all(p, 500, gen);
+    if (!fail) printf("PASS\n");
+    kv_pager_destroy(p);
+    return fail;
+}
+
+int main(void) {
+    strcpy(local_dir, "/tmp/kv_pager_local-XXXXXX");
+    strcpy(remote_dir, "/tmp/kv_pager_remote-XXXXXX");
+    if (!mkdtemp(local_dir) || !mkdtemp(remote_dir)) {
+        perror("mkdtemp");
+        return 1;
+    }
+
+    printf("KV Pager Tests\n\n");
+    int failures = 0, total = 0;
+
+    for (use_arena = 0; use_arena <= 1; use_arena++) {
+        printf("%s%d. %s:\n", use_arena ? "\n" : "", use_arena + 1,
+               use_arena ? "Arena host tier" : "Realloc'd host tier");
+        failures += test_spill_and_reload(); total++;
+        failures += test_store();            total++;
+        failures += test_disk_budget();      total++;
+        failures += test_remove_and_clear(); total++;
+    }
+
+    /* Tier 2 files are unlinked on creation, so the directories are empty. */
+    if (rmdir(local_dir) != 0 || rmdir(remote_dir) != 0) {
+        printf("  tier 2 files left behind in %s or %s\n", local_dir, remote_dir);
+        failures++;
+    }
+
+    printf("\n%s: %d/%d tests passed\n",
+           failures ? "FAILURE" : "SUCCESS", total - failures, total);
+    return failures ? 1 : 0;
+}
@@ -218,6 +218,7 @@ STEPS:
         - OLLAMA_KV_TIER_LOCAL_GB=20    (local budget in GB)
         - OLLAMA_KV_TIER_REMOTE_GB=5000 (remote budget in GB)
         - OLLAMA_KV_TIER_COMPRESS=1     (enable zstd compression)
+        - other OLLAMA_KV_TIER_* knobs  (see diskstore.Config.LoadEnv)
 
 4. Build Ollama:
 